@@ -3,14 +3,16 @@ use actix_web::{http::header, web, App, HttpServer, Responder, HttpResponse};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use std::collections::HashMap;
+use std::env;
 use std::fs;
 use std::io::Write;
 use ethers::prelude::*;
 use std::convert::TryFrom;
 
-// Ethereum provider URL and private key placeholder
-const INFURA_URL: &str = "https://mainnet.infura.io/v3/YOUR_PROJECT_ID";
-const PRIVATE_KEY: &str = "YOUR_PRIVATE_KEY";
+// Local dev chain (anvil / ganache) and the well-known key of its first dev account
+// Override with ETH_RPC_URL and PRIVATE_KEY - never put a real key in the source
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8545";
+const DEV_PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4ff80";
 
 // Task Struct
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -100,8 +102,11 @@ struct AppState {
 // Ethereum Token Transfer Logic
 async fn send_token(req: TokenTransferRequest) -> Result<(), Box<dyn std::error::Error>> {
     // Initialize Ethereum provider
-    let provider = Provider::<Http>::try_from(INFURA_URL)?;
-    let wallet: LocalWallet = PRIVATE_KEY.parse()?;
+    let rpc_url = env::var("ETH_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+    let private_key = env::var("PRIVATE_KEY").unwrap_or_else(|_| DEV_PRIVATE_KEY.to_string());
+    let provider = Provider::<Http>::try_from(rpc_url)?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet: LocalWallet = private_key.parse::<LocalWallet>()?.with_chain_id(chain_id);
     let client = SignerMiddleware::new(provider, wallet);
 
     // Parse Ethereum address and token amount
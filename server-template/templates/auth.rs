@@ -0,0 +1,103 @@
+// User registration, login and logout with bearer session tokens
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct StoredUser {
+    salt: String,
+    password_hash: u64,
+}
+
+#[derive(Default)]
+pub struct AuthState {
+    users: Mutex<HashMap<String, StoredUser>>,
+    // Session token -> username
+    sessions: Mutex<HashMap<String, String>>,
+}
+
+// Stand-in hash so the template has no extra dependencies - use argon2 for real deployments
+fn hash_password(salt: &str, password: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    salt.hash(&mut hasher);
+    password.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+// Username of the session in the Authorization: Bearer <token> header
+pub fn current_user(auth: &AuthState, req: &HttpRequest) -> Option<String> {
+    let token = req
+        .headers()
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    auth.sessions.lock().unwrap().get(token).cloned()
+}
+
+pub async fn register(auth: web::Data<AuthState>, creds: web::Json<Credentials>) -> impl Responder {
+    let mut users = auth.users.lock().unwrap();
+    if users.contains_key(&creds.username) {
+        return HttpResponse::Conflict().body("Username already taken");
+    }
+
+    let salt = random_string(16);
+    let password_hash = hash_password(&salt, &creds.password);
+    users.insert(creds.username.clone(), StoredUser { salt, password_hash });
+    HttpResponse::Ok().finish()
+}
+
+pub async fn login(auth: web::Data<AuthState>, creds: web::Json<Credentials>) -> impl Responder {
+    let users = auth.users.lock().unwrap();
+    match users.get(&creds.username) {
+        Some(user) if user.password_hash == hash_password(&user.salt, &creds.password) => {
+            let token = random_string(32);
+            auth.sessions
+                .lock()
+                .unwrap()
+                .insert(token.clone(), creds.username.clone());
+            HttpResponse::Ok().json(serde_json::json!({ "token": token }))
+        }
+        _ => HttpResponse::Unauthorized().body("Invalid username or password"),
+    }
+}
+
+pub async fn logout(auth: web::Data<AuthState>, req: HttpRequest) -> impl Responder {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) if auth.sessions.lock().unwrap().remove(token).is_some() => {
+            HttpResponse::Ok().finish()
+        }
+        _ => HttpResponse::Unauthorized().finish(),
+    }
+}
+
+// Mounts /register, /login and /logout
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/register", web::post().to(register))
+        .route("/login", web::post().to(login))
+        .route("/logout", web::post().to(logout));
+}
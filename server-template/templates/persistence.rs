@@ -0,0 +1,59 @@
+// JSON file persistence layer
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::sync::Mutex;
+
+// Items of one type keyed by id, written to a JSON file on every change
+pub struct JsonStore<T> {
+    path: String,
+    items: Mutex<HashMap<u64, T>>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> JsonStore<T> {
+    // Loads the file, starting empty when it does not exist yet
+    pub fn open(path: &str) -> Self {
+        let items: HashMap<u64, T> = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        Self {
+            path: path.to_string(),
+            items: Mutex::new(items),
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<T> {
+        self.items.lock().unwrap().get(&id).cloned()
+    }
+
+    pub fn all(&self) -> Vec<T> {
+        self.items.lock().unwrap().values().cloned().collect()
+    }
+
+    // Inserts or replaces the item with the given id
+    pub fn upsert(&self, id: u64, item: T) -> io::Result<()> {
+        let mut items = self.items.lock().unwrap();
+        items.insert(id, item);
+        self.save(&items)
+    }
+
+    pub fn remove(&self, id: u64) -> io::Result<Option<T>> {
+        let mut items = self.items.lock().unwrap();
+        let removed = items.remove(&id);
+        self.save(&items)?;
+        Ok(removed)
+    }
+
+    pub fn next_id(&self) -> u64 {
+        self.items.lock().unwrap().keys().max().map(|id| id + 1).unwrap_or(1)
+    }
+
+    fn save(&self, items: &HashMap<u64, T>) -> io::Result<()> {
+        let contents = serde_json::to_string_pretty(items)?;
+        fs::write(&self.path, contents)
+    }
+}
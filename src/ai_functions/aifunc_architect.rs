@@ -8,24 +8,27 @@ pub fn print_project_scope(_project_description: &str) {
     /// Output: Prints an object response in the following format:
     ///   {
     ///     "is_crud_required": bool, // true if site needs CRUD functionality
-    ///     "is_user_login_and_logout": bool // true if site needs users to be able to log in and log out
-    ///     "is_external_urls_required": bool // true if site needs to fetch data from third part providers
+    ///     "is_user_login_and_logout": bool, // true if site needs users to be able to log in and log out
+    ///     "is_external_urls_required": bool, // true if site needs to fetch data from third part providers
+    ///     "is_blockchain_required": bool // true if site needs smart contracts, tokens or on-chain transactions
     ///   }
     /// Example 1:
     ///   user_request = "I need a full stack website that accepts users and gets stock price data"
     ///   prints:
     ///   {
-    ///     "is_crud_required": true
-    ///     "is_user_login_and_logout": true
-    ///     "is_external_urls_required": bool true
+    ///     "is_crud_required": true,
+    ///     "is_user_login_and_logout": true,
+    ///     "is_external_urls_required": true,
+    ///     "is_blockchain_required": false
     ///   }
     /// Example 2:
     ///   user_request = "I need a simple TODO app"
     ///   prints:
     ///   {
-    ///     "is_crud_required": true
-    ///     "is_user_login_and_logout": false
-    ///     "is_external_urls_required": bool false
+    ///     "is_crud_required": true,
+    ///     "is_user_login_and_logout": false,
+    ///     "is_external_urls_required": false,
+    ///     "is_blockchain_required": false
     ///   }
     println!(OUTPUT)
 }
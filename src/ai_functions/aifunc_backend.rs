@@ -33,6 +33,28 @@ pub fn print_fixed_code(_broken_code_with_bugs: &str) {
     println!(OUTPUT)
 }
 
+#[ai_function]
+pub fn print_persistence_layer_code(_project_description_and_template: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE for the persistence module (src/persistence.rs) of a website backend
+    /// FUNCTION: Adapts the CODE_TEMPLATE so it stores every resource the PROJECT_DESCRIPTION needs to create, read, update and delete
+    /// IMPORTANT: The module is used by main.rs through `mod persistence;`. Only make items pub which main.rs needs.
+    /// IMPORTANT: The following libraries are already installed. Does not use ANY libraries other than what was provided in the template
+    ///   serde, serde_json, tokio, actix-web, rand
+    /// OUTPUT: Print ONLY the code of the module, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
+
+#[ai_function]
+pub fn print_auth_layer_code(_project_description_and_template: &str) {
+    /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE for the authentication module (src/auth.rs) of a website backend
+    /// FUNCTION: Adapts the CODE_TEMPLATE so users can register, log in and log out as the PROJECT_DESCRIPTION requires
+    /// IMPORTANT: The module is used by main.rs through `mod auth;` and mounted with `auth::configure`. Keep `current_user` so main.rs can protect routes.
+    /// IMPORTANT: The following libraries are already installed. Does not use ANY libraries other than what was provided in the template
+    ///   serde, serde_json, tokio, actix-web, rand
+    /// OUTPUT: Print ONLY the code of the module, nothing else. This function ONLY prints code.
+    println!(OUTPUT)
+}
+
 #[ai_function]
 pub fn print_rest_api_endpoints(_code_input: &str) {
     /// INPUT: Takes in Rust webserver CODE_INPUT based on actix-web
@@ -73,7 +95,7 @@ pub fn print_rest_api_endpoints(_code_input: &str) {
     ///   {
     ///     "route": "/item/{id}",
     ///     "is_route_dynamic": "true",
-    ///     "method": "get"
+    ///     "method": "get",
     ///     "request_body": "None",
     ///     "response": {
     ///       "id": "number",
@@ -136,6 +158,14 @@ pub fn print_smart_contract_code(_project_description_and_template: &str) {
     println!(OUTPUT)
 }
 
+#[ai_function]
+pub fn print_fixed_contract_code(_broken_code_with_bugs: &str) {
+    /// INPUT: Takes in a Solidity BROKEN_CODE and the ERROR_BUGS reported by the solc compiler
+    /// FUNCTION: Removes the reported compiler errors from the contract, keeping its contracts, functions and events
+    /// IMPORTANT: Only prints out the complete fixed Solidity contract. No commentary or anything else
+    println!(OUTPUT)
+}
+
 #[ai_function]
 pub fn print_blockchain_integration_code(_project_description_and_template: &str) {
     /// INPUT: Takes in a PROJECT_DESCRIPTION and CODE_TEMPLATE for blockchain interaction
@@ -1,3 +1,4 @@
 pub mod ai_func_managing;
 pub mod aifunc_architect;
 pub mod aifunc_backend;
+pub mod aifunc_frontend;
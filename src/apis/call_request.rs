@@ -1,90 +1,206 @@
-use crate::models::general::llm::{APIResponse, ChatCompletion, MessageAI};
-use dotenv::{dotenv, Error};
-use reqwest::header::{HeaderMap, HeaderValue, InvalidHeaderValue};
-use reqwest::{Client, ClientBuilder, RequestBuilder, Response};
-use serde::Deserialize;
+use crate::apis::llm_provider::{LlmProvider, LlmRequest};
+use crate::models::general::error::WizError;
+use crate::models::general::llm::{APIResponse, ChatCompletion};
+use async_trait::async_trait;
+use dotenv::dotenv;
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::{Client, Response};
 use std::env;
+use std::time::Duration;
 
-// This `derive` requires the `serde` dependency.
-#[derive(Deserialize)]
-struct Ip {
-    origin: String,
+pub const OPEN_AI_URL: &str = "https://api.openai.com/v1/chat/completions";
+pub const OPEN_AI_DEFAULT_MODEL: &str = "gpt-4o-2024-05-13";
+pub const OPEN_AI_DEFAULT_TEMPERATURE: f32 = 0.1;
+
+// OpenAI chat completions provider
+#[derive(Debug, Clone)]
+pub struct OpenAiProvider {
+    url: String,
+    model: String,
+    temperature: f32,
+    client: Client,
 }
 
-// Call large language model (i.e GPT-4 currently using gpt-4o-2024-05-13)
-pub async fn call_gpt(
-    messages: Vec<MessageAI>,
-) -> Result<String, Box<dyn std::error::Error + Send>> {
-    dotenv().ok();
-    // Extend API information
-    let api_key: String =
-        env::var("OPEN_AI_KEY").expect("OPEN_AI_KEY not found in environment variable");
-    let api_org: String =
-        env::var("OPEN_AI_ORG").expect("OPEN_AI_ORG not found in environment variable");
-    // conform our endpoint
-    let url: &str = "https://api.openai.com/v1/chat/completions";
-    // create headers
-    let mut headers: HeaderMap = HeaderMap::new();
-
-    // create api key header
-    headers.insert(
-        "authorization",
-        HeaderValue::from_str(&format!("Bearer {}", api_key)).map_err(
-            |e: InvalidHeaderValue| -> Box<dyn std::error::Error + Send> { Box::new(e) },
-        )?,
-    );
-
-    // create open ai org header
-    headers.insert(
-        "OpenAI-Organization",
-        HeaderValue::from_str(api_org.as_str()).map_err(
-            |e: InvalidHeaderValue| -> Box<dyn std::error::Error + Send> { Box::new(e) },
-        )?,
-    );
-
-    // create client
-    let client: Client = Client::builder()
-        .default_headers(headers)
-        .build()
-        .map_err(|e: reqwest::Error| -> Box<dyn std::error::Error + Send> { Box::new(e) })?;
-
-    // create chat application
-    let chat_completion: ChatCompletion = ChatCompletion {
-        model: "gpt-4o-2024-05-13".to_string(),
-        messages,
-        temperature: 0.1,
-    };
-
-    // extract api response
-    let res: APIResponse = client
-        .post(url)
-        .json(&chat_completion)
-        .send()
-        .await
-        .map_err(|e: reqwest::Error| -> Box<dyn std::error::Error + Send> { Box::new(e) })?
-        .json()
-        .await
-        .map_err(|e: reqwest::Error| -> Box<dyn std::error::Error + Send> { Box::new(e) })?;
-
-    Ok(res.choices[0].message.content.clone())
+impl OpenAiProvider {
+    pub fn new(api_key: String, api_org: Option<String>) -> Result<Self, WizError> {
+        // create headers
+        let mut headers: HeaderMap = HeaderMap::new();
+
+        // create api key header
+        let mut authorization: HeaderValue = HeaderValue::from_str(&format!("Bearer {}", api_key))
+            .map_err(|e| WizError::Config {
+                message: format!("OPEN_AI_KEY: {}", e),
+            })?;
+        authorization.set_sensitive(true);
+        headers.insert("authorization", authorization);
+
+        // create open ai org header
+        if let Some(api_org) = &api_org {
+            headers.insert(
+                "OpenAI-Organization",
+                HeaderValue::from_str(api_org.as_str()).map_err(|e| WizError::Config {
+                    message: format!("OPEN_AI_ORG: {}", e),
+                })?,
+            );
+        }
+
+        // create client, shared by every request of this provider
+        let client: Client = Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(WizError::llm_transport)?;
+
+        Ok(Self {
+            url: OPEN_AI_URL.to_string(),
+            model: OPEN_AI_DEFAULT_MODEL.to_string(),
+            temperature: OPEN_AI_DEFAULT_TEMPERATURE,
+            client,
+        })
+    }
+
+    // Read OPEN_AI_KEY and (optionally) OPEN_AI_ORG / OPEN_AI_MODEL from the environment or .env file
+    pub fn from_env() -> Result<Self, WizError> {
+        dotenv().ok();
+        let api_key: String =
+            env::var("OPEN_AI_KEY").map_err(|e: env::VarError| WizError::Config {
+                message: format!("OPEN_AI_KEY: {}", e),
+            })?;
+        let api_org: Option<String> = env::var("OPEN_AI_ORG")
+            .ok()
+            .filter(|org: &String| !org.is_empty());
+
+        let provider: Self = Self::new(api_key, api_org)?;
+        match env::var("OPEN_AI_MODEL") {
+            Ok(model) if !model.is_empty() => Ok(provider.with_model(&model)),
+            _ => Ok(provider),
+        }
+    }
+
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, WizError> {
+        // create chat application
+        let chat_completion: ChatCompletion = ChatCompletion {
+            model: self.model.clone(),
+            messages: request.messages.clone(),
+            temperature: self.temperature,
+        };
+
+        // send request
+        let response: Response = self
+            .client
+            .post(self.url.as_str())
+            .json(&chat_completion)
+            .send()
+            .await
+            .map_err(WizError::llm_transport)?;
+
+        // surface http errors with their status so the retry policy can classify them
+        let status: u16 = response.status().as_u16();
+        if !response.status().is_success() {
+            let retry_after: Option<Duration> = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .and_then(|value: &str| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let message: String = response.text().await.unwrap_or_default();
+            return Err(WizError::LlmHttp {
+                status,
+                message,
+                retry_after,
+            });
+        }
+
+        // extract api response
+        // a malformed body is not retried, the same request would get the same answer
+        let decode_error = |message: String| WizError::Decode {
+            function_name: request.function_name.clone(),
+            message,
+        };
+        let res: APIResponse = response.json().await.map_err(|e: reqwest::Error| {
+            if e.is_decode() {
+                decode_error(e.to_string())
+            } else {
+                WizError::llm_transport(e)
+            }
+        })?;
+
+        match res.choices.first() {
+            Some(choice) => Ok(choice.message.content.clone()),
+            None => Err(decode_error("response contained no choices".to_string())),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::general::llm::MessageAI;
+
     #[tokio::test]
+    #[ignore = "calls the live OpenAI API"]
     async fn tests_call_to_openai() {
         let message = MessageAI {
             role: "user".to_string(),
             content: "Hi there this is a test. What is web3".to_string(),
         };
-        let messages = vec![message];
-        let res = call_gpt(messages).await;
+        let request = LlmRequest {
+            function_name: "tests_call_to_openai".to_string(),
+            msg_context: "What is web3".to_string(),
+            messages: vec![message],
+        };
+        let provider =
+            OpenAiProvider::from_env().expect("OPEN_AI_KEY not found in environment variable");
+        let res = provider.complete(&request).await;
         if let Ok(res_str) = res {
             dbg!(res_str);
-            assert!(true)
         } else {
-            assert!(false)
+            panic!("Failed to call OpenAI")
         }
     }
+
+    #[tokio::test]
+    async fn tests_malformed_response_is_not_retryable() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: String = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf: [u8; 4096] = [0; 4096];
+            let _ = socket.read(&mut buf).await;
+            let body: &str = "{\"choices\": \"not a list\"}";
+            let response: String = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+
+        let mut provider: OpenAiProvider = OpenAiProvider::new("key".to_string(), None).unwrap();
+        provider.url = url;
+        let request: LlmRequest = LlmRequest {
+            function_name: "print_project_scope".to_string(),
+            msg_context: String::new(),
+            messages: vec![],
+        };
+
+        let err: WizError = provider.complete(&request).await.unwrap_err();
+        assert!(matches!(err, WizError::Decode { .. }), "{}", err);
+        assert!(!err.is_retryable());
+    }
 }
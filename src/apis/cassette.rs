@@ -0,0 +1,209 @@
+use crate::apis::llm_provider::{LlmProvider, LlmRequest};
+use crate::models::general::error::WizError;
+use crate::models::general::llm::MessageAI;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+impl CassetteMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.trim().to_lowercase().as_str() {
+            "record" => Some(Self::Record),
+            "replay" => Some(Self::Replay),
+            _ => None,
+        }
+    }
+}
+
+// One prompt / response pair
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Interaction {
+    pub function_name: String,
+    pub context_hash: String,
+    pub messages: Vec<MessageAI>,
+    pub response: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents: String = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents: String = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)
+    }
+}
+
+// Stable FNV-1a hash of the ai_function input, so cassettes survive compiler upgrades
+pub fn hash_context(msg_context: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in msg_context.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+// Records every exchange with the wrapped provider, or replays a previous recording
+// Exchanges are keyed by ai_function name plus a hash of msg_context.
+// Repeated keys are replayed in the order they were recorded.
+#[derive(Debug)]
+pub struct CassetteProvider {
+    mode: CassetteMode,
+    path: PathBuf,
+    inner: Option<Arc<dyn LlmProvider>>,
+    cassette: Mutex<Cassette>,
+    replay_positions: Mutex<HashMap<(String, String), usize>>,
+}
+
+impl CassetteProvider {
+    // Record exchanges with `inner` to a new cassette at `path`
+    pub fn record(path: &Path, inner: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            mode: CassetteMode::Record,
+            path: path.to_path_buf(),
+            inner: Some(inner),
+            cassette: Mutex::new(Cassette::default()),
+            replay_positions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Serve responses from the cassette at `path`
+    pub fn replay(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            mode: CassetteMode::Replay,
+            path: path.to_path_buf(),
+            inner: None,
+            cassette: Mutex::new(Cassette::load(path)?),
+            replay_positions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn find_recorded(&self, function_name: &str, context_hash: &str) -> Option<String> {
+        let cassette = self.cassette.lock().unwrap();
+        let mut positions = self.replay_positions.lock().unwrap();
+        let position: &mut usize = positions
+            .entry((function_name.to_string(), context_hash.to_string()))
+            .or_insert(0);
+
+        let found: Option<String> = cassette
+            .interactions
+            .iter()
+            .filter(|i| i.function_name == function_name && i.context_hash == context_hash)
+            .nth(*position)
+            .map(|i| i.response.clone());
+
+        if found.is_some() {
+            *position += 1;
+        }
+        found
+    }
+}
+
+#[async_trait]
+impl LlmProvider for CassetteProvider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, WizError> {
+        let context_hash: String = hash_context(&request.msg_context);
+
+        match self.mode {
+            CassetteMode::Replay => self
+                .find_recorded(&request.function_name, &context_hash)
+                .ok_or_else(|| {
+                    WizError::llm_transport(format!(
+                        "Cassette {} has no recorded response for '{}' (context {})",
+                        self.path.display(),
+                        request.function_name,
+                        context_hash
+                    ))
+                }),
+
+            CassetteMode::Record => {
+                let inner: &Arc<dyn LlmProvider> =
+                    self.inner.as_ref().expect("Recording requires a provider");
+                let response: String = inner.complete(request).await?;
+
+                // Save after every exchange so a crashed run still leaves a usable transcript
+                let mut cassette = self.cassette.lock().unwrap();
+                cassette.interactions.push(Interaction {
+                    function_name: request.function_name.clone(),
+                    context_hash,
+                    messages: request.messages.clone(),
+                    response: response.clone(),
+                });
+                cassette
+                    .save(&self.path)
+                    .map_err(|e: io::Error| WizError::file_io(self.path.display(), e))?;
+
+                Ok(response)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::mock_provider::MockProvider;
+
+    fn request(function_name: &str, msg_context: &str) -> LlmRequest {
+        LlmRequest {
+            function_name: function_name.to_string(),
+            msg_context: msg_context.to_string(),
+            messages: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_cassette_record_and_replay() {
+        let path: PathBuf = std::env::temp_dir().join(format!(
+            "wizai_cassette_{}_record_and_replay.json",
+            std::process::id()
+        ));
+
+        let mock: MockProvider = MockProvider::new()
+            .with_response("print_fixed_code", "fix one")
+            .with_response("print_fixed_code", "fix two");
+        let recorder: CassetteProvider = CassetteProvider::record(&path, Arc::new(mock));
+
+        let req: LlmRequest = request("print_fixed_code", "BROKEN_CODE: fn main() {");
+        assert_eq!(recorder.complete(&req).await.unwrap(), "fix one");
+        assert_eq!(recorder.complete(&req).await.unwrap(), "fix two");
+
+        let player: CassetteProvider = CassetteProvider::replay(&path).unwrap();
+        assert_eq!(player.complete(&req).await.unwrap(), "fix one");
+        assert_eq!(player.complete(&req).await.unwrap(), "fix two");
+        assert!(player.complete(&req).await.is_err());
+
+        // A different input is not in the recording
+        let other: LlmRequest = request("print_fixed_code", "BROKEN_CODE: fn other() {");
+        assert!(player.complete(&other).await.is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn tests_hash_context_is_stable() {
+        assert_eq!(hash_context(""), "cbf29ce484222325");
+        assert_ne!(hash_context("a"), hash_context("b"));
+    }
+}
@@ -0,0 +1,448 @@
+use crate::helpers::config::WizConfig;
+use crate::helpers::process_output::OutputDrain;
+use crate::models::general::error::WizError;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tiny_keccak::{Hasher, Keccak};
+use tokio::time;
+
+// How long a dev chain which exited during startup gets to flush its last output
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Local Ethereum dev chain reached over JSON-RPC
+// Transactions are sent from its unlocked dev accounts (the well-known anvil/hardhat
+// keys of the "test test ... junk" mnemonic), so nothing has to be signed here.
+#[derive(Debug)]
+pub struct DevChain {
+    url: String,
+    client: Client,
+    process: Option<Child>,
+    // stderr of the started process, read while it runs
+    stderr: Option<OutputDrain>,
+}
+
+impl DevChain {
+    // Use a dev chain which is already running
+    pub fn connect(url: &str) -> Result<Self, WizError> {
+        let client: Client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e: reqwest::Error| WizError::dev_chain(format!("{}: {}", url, e)))?;
+
+        Ok(Self {
+            url: url.to_string(),
+            client,
+            process: None,
+            stderr: None,
+        })
+    }
+
+    // Start the configured dev chain binary and wait until it answers
+    // The process is killed when the DevChain is dropped.
+    pub async fn start(config: &WizConfig) -> Result<Self, WizError> {
+        let url: String = config.dev_chain_url();
+        let mut dev_chain: Self = Self::connect(&url)?;
+
+        // Never deploy to whatever else happens to listen on the port
+        if dev_chain.chain_id().await.is_ok() {
+            return Err(WizError::dev_chain(format!(
+                "port {} is already in use",
+                config.contracts.dev_chain_port
+            )));
+        }
+
+        let command: String = config.contracts.dev_chain.display().to_string();
+        let mut process: Child = Command::new(&config.contracts.dev_chain)
+            .arg("--port")
+            .arg(config.contracts.dev_chain_port.to_string())
+            .args(&config.contracts.dev_chain_args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| WizError::process(&command, e))?;
+        dev_chain.stderr = Some(OutputDrain::spawn(process.stderr.take()));
+        dev_chain.process = Some(process);
+
+        for _ in 0..50 {
+            if dev_chain.chain_id().await.is_ok() {
+                return Ok(dev_chain);
+            }
+
+            if let Some(process) = dev_chain.process.as_mut() {
+                let exited: bool = process
+                    .try_wait()
+                    .map_err(|e| WizError::process(&command, e))?
+                    .is_some();
+                if exited {
+                    let stderr: String = dev_chain
+                        .stderr
+                        .as_mut()
+                        .map(|stderr: &mut OutputDrain| stderr.finish(STDERR_DRAIN_TIMEOUT))
+                        .unwrap_or_default();
+                    return Err(WizError::dev_chain(format!(
+                        "{} exited: {}",
+                        command,
+                        stderr.trim()
+                    )));
+                }
+            }
+            time::sleep(Duration::from_millis(200)).await;
+        }
+
+        Err(WizError::dev_chain(format!(
+            "{} did not answer on {}",
+            command, url
+        )))
+    }
+
+    // JSON-RPC call, an error object in the reply becomes Ok(Err(message))
+    pub async fn rpc(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Result<Value, String>, WizError> {
+        let request: Value = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let reply: Value = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| WizError::dev_chain(format!("{} {}: {}", method, self.url, e)))?
+            .json()
+            .await
+            .map_err(|e| WizError::dev_chain(format!("{}: {}", method, e)))?;
+
+        if let Some(error) = reply.get("error") {
+            let message: String = error
+                .get("message")
+                .and_then(|message| message.as_str())
+                .map(|message| message.to_string())
+                .unwrap_or_else(|| error.to_string());
+            return Ok(Err(message));
+        }
+        Ok(Ok(reply.get("result").cloned().unwrap_or(Value::Null)))
+    }
+
+    // Like rpc, for methods which are not expected to fail
+    async fn rpc_ok(&self, method: &str, params: Value) -> Result<Value, WizError> {
+        self.rpc(method, params)
+            .await?
+            .map_err(|message| WizError::dev_chain(format!("{}: {}", method, message)))
+    }
+
+    pub async fn chain_id(&self) -> Result<Value, WizError> {
+        self.rpc_ok("eth_chainId", json!([])).await
+    }
+
+    // Funded dev accounts of the chain
+    pub async fn accounts(&self) -> Result<Vec<String>, WizError> {
+        let accounts: Value = self.rpc_ok("eth_accounts", json!([])).await?;
+        serde_json::from_value(accounts)
+            .map_err(|e| WizError::dev_chain(format!("eth_accounts: {}", e)))
+    }
+
+    // Deploys creation bytecode and returns the contract address, or why it was rejected
+    pub async fn deploy(
+        &self,
+        from: &str,
+        bytecode: &str,
+    ) -> Result<Result<String, String>, WizError> {
+        let tx: Value = json!({ "from": from, "data": hex_prefixed(bytecode) });
+        let receipt: Value = match self.send_transaction(tx).await? {
+            Ok(receipt) => receipt,
+            Err(reason) => return Ok(Err(reason)),
+        };
+
+        match receipt
+            .get("contractAddress")
+            .and_then(|address| address.as_str())
+        {
+            Some(address) => Ok(Ok(address.to_string())),
+            None => Ok(Err("receipt has no contract address".to_string())),
+        }
+    }
+
+    // eth_call against the latest block, returns the raw hex output or the revert reason
+    pub async fn call(&self, to: &str, data: &str) -> Result<Result<String, String>, WizError> {
+        let call: Value = json!({ "to": to, "data": data });
+        let output: Result<Value, String> = self.rpc("eth_call", json!([call, "latest"])).await?;
+        Ok(output.map(|output| output.as_str().unwrap_or_default().to_string()))
+    }
+
+    // Sends a transaction and waits for its receipt, reverted transactions are Ok(Err(reason))
+    pub async fn transact(
+        &self,
+        from: &str,
+        to: &str,
+        data: &str,
+    ) -> Result<Result<Value, String>, WizError> {
+        let tx: Value = json!({ "from": from, "to": to, "data": data });
+        self.send_transaction(tx).await
+    }
+
+    async fn send_transaction(&self, tx: Value) -> Result<Result<Value, String>, WizError> {
+        let tx_hash: Value = match self.rpc("eth_sendTransaction", json!([tx])).await? {
+            Ok(tx_hash) => tx_hash,
+            Err(reason) => return Ok(Err(reason)),
+        };
+
+        // Dev chains mine instantly, the receipt should show up right away
+        for _ in 0..50 {
+            let receipt: Value = self
+                .rpc_ok("eth_getTransactionReceipt", json!([tx_hash]))
+                .await?;
+            if !receipt.is_null() {
+                let status: &str = receipt
+                    .get("status")
+                    .and_then(|status| status.as_str())
+                    .unwrap_or("0x1");
+                if status == "0x0" {
+                    return Ok(Err(format!("transaction {} reverted", tx_hash)));
+                }
+                return Ok(Ok(receipt));
+            }
+            time::sleep(Duration::from_millis(100)).await;
+        }
+
+        Err(WizError::dev_chain(format!(
+            "transaction {} was never mined",
+            tx_hash
+        )))
+    }
+}
+
+impl Drop for DevChain {
+    fn drop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+    }
+}
+
+fn hex_prefixed(hex: &str) -> String {
+    if hex.starts_with("0x") {
+        hex.to_string()
+    } else {
+        format!("0x{}", hex)
+    }
+}
+
+// First 4 bytes of keccak256 of a function signature, i.e. "transfer(address,uint256)"
+pub fn function_selector(signature: &str) -> String {
+    let mut hasher: Keccak = Keccak::v256();
+    let mut hash: [u8; 32] = [0; 32];
+    hasher.update(signature.as_bytes());
+    hasher.finalize(&mut hash);
+    format!(
+        "0x{}",
+        hash[..4]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+// ABI encoded address argument (left padded to 32 bytes)
+pub fn encode_address(address: &str) -> String {
+    format!("{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
+
+// ABI encoded uint256 argument
+pub fn encode_uint(value: u64) -> String {
+    format!("{:064x}", value)
+}
+
+// First word of an ABI encoded output as a number (saturating for values above u128)
+pub fn decode_uint(output: &str) -> Option<u128> {
+    let word: &str = output.trim_start_matches("0x").get(..64)?;
+    let significant: &str = word.trim_start_matches('0');
+    if significant.len() > 32 {
+        return Some(u128::MAX);
+    }
+    if significant.is_empty() {
+        return Some(0);
+    }
+    u128::from_str_radix(significant, 16).ok()
+}
+
+// Minimal JSON-RPC dev chain for tests: two accounts, instant receipts and successful calls
+// Calls to `broken()` revert.
+#[cfg(test)]
+pub async fn spawn_fake_dev_chain() -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url: String = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return;
+            };
+            tokio::spawn(async move {
+                let mut buf: Vec<u8> = vec![];
+                let mut chunk: [u8; 4096] = [0; 4096];
+                loop {
+                    let n: usize = socket.read(&mut chunk).await.unwrap_or(0);
+                    if n == 0 {
+                        return;
+                    }
+                    buf.extend_from_slice(&chunk[..n]);
+
+                    let text: String = String::from_utf8_lossy(&buf).to_string();
+                    let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                        continue;
+                    };
+                    let content_length: usize = head
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            if name.eq_ignore_ascii_case("content-length") {
+                                value.trim().parse().ok()
+                            } else {
+                                None
+                            }
+                        })
+                        .unwrap_or(0);
+                    if body.len() < content_length {
+                        continue;
+                    }
+
+                    let request: Value = serde_json::from_str(&body[..content_length]).unwrap();
+                    let reply: Value = fake_rpc_reply(&request);
+                    let reply_str: String = reply.to_string();
+                    let response: String = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                        reply_str.len(),
+                        reply_str
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                    buf.clear();
+                }
+            });
+        }
+    });
+    url
+}
+
+#[cfg(test)]
+fn fake_rpc_reply(request: &Value) -> Value {
+    let params: &Value = &request["params"];
+    let result: Result<Value, &str> = match request["method"].as_str().unwrap_or_default() {
+        "eth_chainId" => Ok(json!("0x7a69")),
+        "eth_accounts" => Ok(json!([
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+            "0x70997970c51812dc3a010c7d01b50e0d17dc79c8"
+        ])),
+        "eth_sendTransaction" if params[0].get("to").is_none() => Ok(json!("0x01")),
+        "eth_sendTransaction" => Ok(json!("0x02")),
+        "eth_getTransactionReceipt" if params[0] == "0x01" => Ok(json!({
+            "status": "0x1",
+            "contractAddress": "0x5fbdb2315678afecb367f032d93f642f64180aa3"
+        })),
+        "eth_getTransactionReceipt" => Ok(json!({ "status": "0x1", "contractAddress": null })),
+        "eth_call" => {
+            let data: &str = params[0]["data"].as_str().unwrap_or_default();
+            if data.starts_with(&function_selector("broken()")) {
+                Err("execution reverted")
+            } else {
+                Ok(json!(format!("0x{}", encode_uint(1000))))
+            }
+        }
+        _ => Err("method not found"),
+    };
+
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        Err(message) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32000, "message": message }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::net::TcpListener;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use std::process;
+
+    #[test]
+    fn tests_abi_encoding() {
+        assert_eq!(function_selector("transfer(address,uint256)"), "0xa9059cbb");
+        assert_eq!(function_selector("balanceOf(address)"), "0x70a08231");
+        assert_eq!(
+            encode_address("0x70997970C51812dc3A010C7d01b50e0d17dc79C8"),
+            "00000000000000000000000070997970c51812dc3a010c7d01b50e0d17dc79c8"
+        );
+        assert_eq!(decode_uint(&format!("0x{}", encode_uint(1000))), Some(1000));
+        assert_eq!(decode_uint("0x"), None);
+    }
+
+    #[tokio::test]
+    async fn tests_start_drains_chatty_dev_chain() {
+        // Far more stderr than a pipe holds before it gives up: blocked on a full pipe it would
+        // never exit and start would time out instead
+        let dir: PathBuf = env::temp_dir().join(format!("wizai_dev_chain_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let script: PathBuf = dir.join("fake-anvil");
+        fs::write(
+            &script,
+            "#!/bin/sh\nhead -c 300000 /dev/zero | tr '\\0' x >&2\necho 'address already in use' >&2\nexit 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut config: WizConfig = WizConfig::resolve(None).expect("Failed to load wizai.toml");
+        config.contracts.dev_chain = script;
+        config.contracts.dev_chain_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let err: WizError = DevChain::start(&config).await.unwrap_err();
+        let message: String = err.to_string();
+        assert!(message.contains("[earlier output dropped]"), "{}", message);
+        assert!(message.ends_with("address already in use"), "{}", message);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_deploy_and_call_on_dev_chain() {
+        let dev_chain: DevChain = DevChain::connect(&spawn_fake_dev_chain().await).unwrap();
+
+        let accounts: Vec<String> = dev_chain.accounts().await.unwrap();
+        assert_eq!(accounts.len(), 2);
+
+        let address: String = dev_chain
+            .deploy(&accounts[0], "6080604052")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(address, "0x5fbdb2315678afecb367f032d93f642f64180aa3");
+
+        let reverted: Result<String, String> = dev_chain
+            .call(&address, &function_selector("broken()"))
+            .await
+            .unwrap();
+        assert_eq!(reverted, Err("execution reverted".to_string()));
+    }
+}
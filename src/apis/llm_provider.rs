@@ -0,0 +1,21 @@
+use crate::models::general::error::WizError;
+use crate::models::general::llm::MessageAI;
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+// A single prompt sent to a provider
+#[derive(Debug, Clone)]
+pub struct LlmRequest {
+    // Name of the ai_function being called (from get_function_string!)
+    pub function_name: String,
+    // Input passed to the ai_function (before it is extended into a prompt)
+    pub msg_context: String,
+    pub messages: Vec<MessageAI>,
+}
+
+// Large language model backend used by every agent
+#[async_trait]
+pub trait LlmProvider: Debug + Send + Sync {
+    // Sends the chat messages to the model and returns the content of the first choice
+    async fn complete(&self, request: &LlmRequest) -> Result<String, WizError>;
+}
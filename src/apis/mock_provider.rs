@@ -0,0 +1,142 @@
+use crate::apis::llm_provider::{LlmProvider, LlmRequest};
+use crate::models::general::error::WizError;
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+#[cfg(test)]
+pub const MOCK_FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/llm");
+
+// Scripted provider which answers by ai_function name - no network involved
+// Each function has a queue of responses. The last response is repeated once the queue is drained.
+#[derive(Debug, Default)]
+pub struct MockProvider {
+    responses: Mutex<HashMap<String, VecDeque<String>>>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Queue a response for the given ai_function
+    pub fn with_response(self, function_name: &str, response: &str) -> Self {
+        self.responses
+            .lock()
+            .unwrap()
+            .entry(function_name.to_string())
+            .or_default()
+            .push_back(response.to_string());
+        self
+    }
+
+    // Load responses from a fixture directory
+    // File stems are ai_function names: `print_fixed_code.txt` or `print_fixed_code.1.txt`, `print_fixed_code.2.txt` ...
+    pub fn from_fixture_dir(dir: &Path) -> io::Result<Self> {
+        let mut fixtures: Vec<(String, u32, String)> = vec![];
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("txt") {
+                continue;
+            }
+
+            let stem: &str = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let (function_name, order) = match stem.rsplit_once('.') {
+                Some((name, idx)) => match idx.parse::<u32>() {
+                    Ok(order) => (name.to_string(), order),
+                    Err(_) => (stem.to_string(), 0),
+                },
+                None => (stem.to_string(), 0),
+            };
+
+            fixtures.push((function_name, order, fs::read_to_string(&path)?));
+        }
+
+        fixtures.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+
+        let provider: Self = Self::new();
+        Ok(fixtures
+            .iter()
+            .fold(provider, |provider, (name, _, response)| {
+                provider.with_response(name, response)
+            }))
+    }
+
+    // Fixtures shipped with the repository
+    #[cfg(test)]
+    pub fn from_default_fixtures() -> io::Result<Self> {
+        Self::from_fixture_dir(Path::new(MOCK_FIXTURES_DIR))
+    }
+
+    // ai_function names in the order they were called
+    #[cfg(test)]
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, WizError> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(request.function_name.clone());
+
+        let mut responses = self.responses.lock().unwrap();
+        let queue: Option<&mut VecDeque<String>> = responses.get_mut(&request.function_name);
+
+        match queue {
+            Some(queue) if queue.len() > 1 => Ok(queue.pop_front().unwrap()),
+            Some(queue) if queue.len() == 1 => Ok(queue[0].clone()),
+            _ => Err(WizError::llm_transport(format!(
+                "MockProvider has no response scripted for '{}'",
+                request.function_name
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(function_name: &str) -> LlmRequest {
+        LlmRequest {
+            function_name: function_name.to_string(),
+            msg_context: String::new(),
+            messages: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_mock_provider_scripted_responses() {
+        let provider: MockProvider = MockProvider::new()
+            .with_response("print_fixed_code", "first")
+            .with_response("print_fixed_code", "second");
+
+        let req: LlmRequest = request("print_fixed_code");
+        assert_eq!(provider.complete(&req).await.unwrap(), "first");
+        assert_eq!(provider.complete(&req).await.unwrap(), "second");
+        assert_eq!(provider.complete(&req).await.unwrap(), "second");
+        assert!(provider.complete(&request("unknown")).await.is_err());
+        assert_eq!(provider.calls().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn tests_mock_provider_default_fixtures() {
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load fixtures");
+
+        let res: String = provider
+            .complete(&request("print_project_scope"))
+            .await
+            .unwrap();
+        assert!(res.contains("is_crud_required"));
+    }
+}
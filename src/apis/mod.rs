@@ -1 +1,6 @@
 pub mod call_request;
+pub mod cassette;
+pub mod dev_chain;
+pub mod llm_provider;
+pub mod mock_provider;
+pub mod retry;
@@ -0,0 +1,244 @@
+use crate::apis::llm_provider::{LlmProvider, LlmRequest};
+use crate::helpers::command_line::PrintCommand;
+use crate::models::general::error::WizError;
+use async_trait::async_trait;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+
+// How failed LLM calls are retried
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    // Total number of calls, including the first one
+    pub max_attempts: u32,
+    // Delay before the first retry, doubled on every following retry
+    pub base_delay: Duration,
+    // Upper bound for a single delay (also caps Retry-After)
+    pub max_delay: Duration,
+    // Randomise each delay between half and all of its value
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    // Delay before the retry following failed attempt number `attempt` (starting at 1)
+    pub fn delay_for(&self, attempt: u32, error: &WizError) -> Duration {
+        // Rate limited: the server tells us how long to wait
+        if let WizError::LlmHttp {
+            retry_after: Some(retry_after),
+            ..
+        } = error
+        {
+            return (*retry_after).min(self.max_delay);
+        }
+
+        let exponent: u32 = attempt.saturating_sub(1).min(16);
+        let delay: Duration = self
+            .base_delay
+            .saturating_mul(2u32.pow(exponent))
+            .min(self.max_delay);
+
+        if self.jitter {
+            let fraction: f64 = 0.5 + 0.5 * random_unit();
+            delay.mul_f64(fraction)
+        } else {
+            delay
+        }
+    }
+}
+
+// Random number in [0, 1) without pulling in an rng crate
+fn random_unit() -> f64 {
+    let random: u64 = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Wraps a provider and retries transient failures according to a RetryPolicy
+// Non retryable errors (i.e. HTTP 400/401/404) are returned immediately.
+#[derive(Debug)]
+pub struct RetryProvider {
+    inner: Arc<dyn LlmProvider>,
+    policy: RetryPolicy,
+}
+
+impl RetryProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RetryProvider {
+    async fn complete(&self, request: &LlmRequest) -> Result<String, WizError> {
+        let mut attempt: u32 = 1;
+        loop {
+            match self.inner.complete(request).await {
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_retryable() && attempt < self.policy.max_attempts => {
+                    let delay: Duration = self.policy.delay_for(attempt, &e);
+                    let retry_msg: String = format!(
+                        "{} (attempt {}/{}), retrying in {:.1}s",
+                        e,
+                        attempt,
+                        self.policy.max_attempts,
+                        delay.as_secs_f32()
+                    );
+                    PrintCommand::Issue
+                        .print_agent_message(request.function_name.as_str(), retry_msg.as_str());
+
+                    time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    // Fails with the scripted errors first, then succeeds
+    #[derive(Debug)]
+    struct FlakyProvider {
+        failures: Mutex<VecDeque<WizError>>,
+        calls: Mutex<u32>,
+    }
+
+    impl FlakyProvider {
+        fn new(failures: Vec<WizError>) -> Self {
+            Self {
+                failures: Mutex::new(failures.into()),
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for FlakyProvider {
+        async fn complete(&self, _request: &LlmRequest) -> Result<String, WizError> {
+            *self.calls.lock().unwrap() += 1;
+            match self.failures.lock().unwrap().pop_front() {
+                Some(e) => Err(e),
+                None => Ok("done".to_string()),
+            }
+        }
+    }
+
+    fn http_error(status: u16) -> WizError {
+        WizError::LlmHttp {
+            status,
+            message: String::new(),
+            retry_after: None,
+        }
+    }
+
+    fn instant_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        }
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            function_name: "print_fixed_code".to_string(),
+            msg_context: String::new(),
+            messages: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_retries_rate_limits_and_server_errors() {
+        let flaky: Arc<FlakyProvider> = Arc::new(FlakyProvider::new(vec![
+            http_error(429),
+            http_error(503),
+            WizError::llm_transport("connection reset"),
+        ]));
+        let provider: RetryProvider = RetryProvider::new(flaky.clone(), instant_policy(4));
+
+        assert_eq!(provider.complete(&request()).await.unwrap(), "done");
+        assert_eq!(flaky.calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn tests_does_not_retry_client_errors() {
+        let flaky: Arc<FlakyProvider> = Arc::new(FlakyProvider::new(vec![http_error(401)]));
+        let provider: RetryProvider = RetryProvider::new(flaky.clone(), instant_policy(4));
+
+        assert!(matches!(
+            provider.complete(&request()).await,
+            Err(WizError::LlmHttp { status: 401, .. })
+        ));
+        assert_eq!(flaky.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn tests_gives_up_after_max_attempts() {
+        let flaky: Arc<FlakyProvider> = Arc::new(FlakyProvider::new(vec![
+            http_error(500),
+            http_error(500),
+            http_error(500),
+        ]));
+        let provider: RetryProvider = RetryProvider::new(flaky.clone(), instant_policy(2));
+
+        assert!(provider.complete(&request()).await.is_err());
+        assert_eq!(flaky.calls(), 2);
+    }
+
+    #[test]
+    fn tests_backoff_delays() {
+        let policy: RetryPolicy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            jitter: false,
+        };
+        let err: WizError = http_error(503);
+        assert_eq!(policy.delay_for(1, &err), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(3, &err), Duration::from_secs(4));
+        assert_eq!(policy.delay_for(8, &err), Duration::from_secs(10));
+
+        // Retry-After wins over the exponential delay
+        let rate_limited: WizError = WizError::LlmHttp {
+            status: 429,
+            message: String::new(),
+            retry_after: Some(Duration::from_secs(7)),
+        };
+        assert_eq!(policy.delay_for(1, &rate_limited), Duration::from_secs(7));
+
+        // Jitter keeps the delay between half and all of the backoff
+        let jittered: Duration = RetryPolicy {
+            jitter: true,
+            ..policy
+        }
+        .delay_for(3, &err);
+        assert!(jittered >= Duration::from_secs(2) && jittered <= Duration::from_secs(4));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::helpers::approval::ApprovalPolicy;
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Command line interface of wizai
+#[derive(Debug, Parser)]
+#[command(
+    name = "wizai",
+    version,
+    about = "Builds web servers from a prompt with LLM agents"
+)]
+pub struct Cli {
+    /// wizai.toml to use instead of the discovered one
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Workspace the project is generated in (overrides [workspace] root)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Never read from stdin: the prompt must be passed as an argument and
+    /// the always-ask approval policy declines
+    #[arg(long, global = true)]
+    pub non_interactive: bool,
+
+    /// Continue the run saved in the checkpoint, skipping agents which already finished
+    #[arg(long, global = true)]
+    pub resume: bool,
+
+    /// How running AI generated code is approved (overrides [approval] policy)
+    #[arg(long, global = true, value_enum, value_name = "POLICY")]
+    pub approval: Option<ApprovalPolicy>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum Command {
+    /// Run the full pipeline from a prompt
+    New(PromptArgs),
+    /// Describe and scope the project, stopping after the Solutions Architect
+    Plan(PromptArgs),
+    /// Run the Backend Developer and its planned layers against the factsheet of a previous run
+    Build,
+    /// Start the generated server and call the endpoints of the saved API schema
+    Test,
+    /// Extract the API schema from the generated server again
+    Schema,
+}
+
+#[derive(Debug, Args, PartialEq)]
+pub struct PromptArgs {
+    /// What website to build
+    #[arg(conflicts_with = "prompt_file")]
+    pub prompt: Option<String>,
+
+    /// Read the prompt from a file
+    #[arg(long, value_name = "PATH")]
+    pub prompt_file: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn tests_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn tests_parse_subcommands_and_global_flags() {
+        let cli: Cli = Cli::try_parse_from([
+            "wizai",
+            "new",
+            "a site that tracks my workouts",
+            "--non-interactive",
+            "--output-dir",
+            "out",
+            "--approval",
+            "auto-approve-in-sandbox",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.approval, Some(ApprovalPolicy::AutoApproveInSandbox));
+        assert!(cli.non_interactive);
+        assert_eq!(cli.output_dir, Some(PathBuf::from("out")));
+        assert_eq!(
+            cli.command,
+            Command::New(PromptArgs {
+                prompt: Some("a site that tracks my workouts".to_string()),
+                prompt_file: None,
+            })
+        );
+
+        let cli: Cli = Cli::try_parse_from(["wizai", "build", "--resume"]).unwrap();
+        assert!(cli.resume);
+        assert_eq!(cli.command, Command::Build);
+
+        let cli: Cli =
+            Cli::try_parse_from(["wizai", "--config", "ci/wizai.toml", "schema"]).unwrap();
+        assert_eq!(cli.config, Some(PathBuf::from("ci/wizai.toml")));
+        assert_eq!(cli.command, Command::Schema);
+    }
+
+    #[test]
+    fn tests_prompt_and_prompt_file_conflict() {
+        let res = Cli::try_parse_from(["wizai", "plan", "hello", "--prompt-file", "p.txt"]);
+        assert!(res.is_err());
+    }
+}
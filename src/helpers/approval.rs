@@ -0,0 +1,141 @@
+use crate::helpers::command_line::{confirm_safe_code, PrintCommand};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// How requests to run AI generated code are answered
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApprovalPolicy {
+    // Ask on the terminal every time
+    #[default]
+    AlwaysAsk,
+    // Approve when running inside a container or sandbox, deny otherwise
+    AutoApproveInSandbox,
+    // Never run generated code
+    Deny,
+}
+
+impl ApprovalPolicy {
+    pub fn hook(self) -> Arc<dyn ApprovalHook> {
+        match self {
+            Self::AlwaysAsk => Arc::new(TerminalApproval),
+            Self::AutoApproveInSandbox => Arc::new(SandboxApproval {
+                in_sandbox: in_sandbox(),
+            }),
+            Self::Deny => Arc::new(DenyApproval),
+        }
+    }
+
+    // Asking is impossible without a terminal, so it becomes a denial
+    pub fn non_interactive(self) -> Self {
+        match self {
+            Self::AlwaysAsk => Self::Deny,
+            other => other,
+        }
+    }
+}
+
+// Something an agent wants to do which needs approval first
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalRequest {
+    pub position: String,
+    pub action: String,
+    pub project_path: PathBuf,
+}
+
+// Decides whether an agent may run AI generated code
+pub trait ApprovalHook: Debug + Send + Sync {
+    fn approve(&self, request: &ApprovalRequest) -> bool;
+
+    // Shown to the user when the request is declined
+    fn decline_reason(&self) -> String {
+        "AI generated code was not approved to run".to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct TerminalApproval;
+
+impl ApprovalHook for TerminalApproval {
+    fn approve(&self, request: &ApprovalRequest) -> bool {
+        let request_msg: String = format!(
+            "Requesting approval to {} in {}",
+            request.action,
+            request.project_path.display()
+        );
+        PrintCommand::UnitTest.print_agent_message(&request.position, request_msg.as_str());
+        confirm_safe_code()
+    }
+}
+
+#[derive(Debug)]
+pub struct SandboxApproval {
+    in_sandbox: bool,
+}
+
+impl ApprovalHook for SandboxApproval {
+    fn approve(&self, request: &ApprovalRequest) -> bool {
+        if self.in_sandbox {
+            let approve_msg: String = format!("Auto approved (sandbox): {}", request.action);
+            PrintCommand::UnitTest.print_agent_message(&request.position, approve_msg.as_str());
+        }
+        self.in_sandbox
+    }
+
+    fn decline_reason(&self) -> String {
+        "AI generated code is only auto approved inside a sandbox (set WIZAI_SANDBOX=1 in one)"
+            .to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct DenyApproval;
+
+impl ApprovalHook for DenyApproval {
+    fn approve(&self, _request: &ApprovalRequest) -> bool {
+        false
+    }
+
+    fn decline_reason(&self) -> String {
+        "running AI generated code is denied by the approval policy".to_string()
+    }
+}
+
+// Containers (docker, podman) or an explicit WIZAI_SANDBOX=1
+fn in_sandbox() -> bool {
+    let flagged: bool = matches!(env::var("WIZAI_SANDBOX").as_deref(), Ok("1") | Ok("true"));
+    flagged || Path::new("/.dockerenv").exists() || Path::new("/run/.containerenv").exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ApprovalRequest {
+        ApprovalRequest {
+            position: "Backend Developer".to_string(),
+            action: "build and run the generated web server".to_string(),
+            project_path: PathBuf::from("server-template"),
+        }
+    }
+
+    #[test]
+    fn tests_policy_decisions() {
+        assert!(!ApprovalPolicy::Deny.hook().approve(&request()));
+        assert!(SandboxApproval { in_sandbox: true }.approve(&request()));
+        assert!(!SandboxApproval { in_sandbox: false }.approve(&request()));
+
+        assert_eq!(
+            ApprovalPolicy::AlwaysAsk.non_interactive(),
+            ApprovalPolicy::Deny
+        );
+        assert_eq!(
+            ApprovalPolicy::AutoApproveInSandbox.non_interactive(),
+            ApprovalPolicy::AutoApproveInSandbox
+        );
+    }
+}
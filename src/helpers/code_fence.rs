@@ -0,0 +1,153 @@
+// Language tags of the code blocks to keep
+pub const RUST_TAGS: [&str; 2] = ["rust", "rs"];
+pub const JSON_TAGS: [&str; 1] = ["json"];
+pub const HTML_TAGS: [&str; 2] = ["html", "htm"];
+pub const SOLIDITY_TAGS: [&str; 2] = ["solidity", "sol"];
+
+struct CodeBlock {
+    language: String,
+    code: String,
+}
+
+// Split a markdown response into its fenced code blocks (``` or ~~~)
+// An unterminated block runs until the end of the response.
+fn parse_code_blocks(response: &str) -> Vec<CodeBlock> {
+    let mut blocks: Vec<CodeBlock> = vec![];
+    let mut current: Option<(String, String, Vec<&str>)> = None;
+
+    for line in response.lines() {
+        let trimmed: &str = line.trim_start();
+
+        match current.take() {
+            None => {
+                if let Some(fence) = opening_fence(trimmed) {
+                    let language: String = trimmed[fence.len()..]
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or("")
+                        .to_lowercase();
+                    current = Some((fence, language, vec![]));
+                }
+            }
+            Some((fence, language, mut lines)) => {
+                let is_closing: bool =
+                    trimmed.starts_with(fence.as_str()) && trimmed.trim_end() == fence;
+                if is_closing {
+                    blocks.push(CodeBlock {
+                        language,
+                        code: lines.join("\n"),
+                    });
+                } else {
+                    lines.push(line);
+                    current = Some((fence, language, lines));
+                }
+            }
+        }
+    }
+
+    if let Some((_, language, lines)) = current {
+        blocks.push(CodeBlock {
+            language,
+            code: lines.join("\n"),
+        });
+    }
+    blocks
+}
+
+// Returns the fence (i.e. "```" or "~~~~") the line opens with
+fn opening_fence(line: &str) -> Option<String> {
+    let fence_char: char = line
+        .chars()
+        .next()
+        .filter(|c: &char| *c == '`' || *c == '~')?;
+    let fence: String = line
+        .chars()
+        .take_while(|c: &char| *c == fence_char)
+        .collect();
+    if fence.len() >= 3 {
+        Some(fence)
+    } else {
+        None
+    }
+}
+
+// Keep only the code of an LLM response
+// Code blocks tagged with one of `language_tags` win, then untagged blocks.
+// Blocks are joined in order and any prose around them is dropped.
+// A response without code fences is assumed to be code already.
+pub fn extract_code(response: &str, language_tags: &[&str]) -> String {
+    let blocks: Vec<CodeBlock> = parse_code_blocks(response);
+    if blocks.is_empty() {
+        return format!("{}\n", response.trim());
+    }
+
+    let tagged: Vec<&CodeBlock> = blocks
+        .iter()
+        .filter(|block: &&CodeBlock| language_tags.contains(&block.language.as_str()))
+        .collect();
+    let selected: Vec<&CodeBlock> = if tagged.is_empty() {
+        blocks
+            .iter()
+            .filter(|block: &&CodeBlock| block.language.is_empty())
+            .collect()
+    } else {
+        tagged
+    };
+
+    // Only blocks in other languages: better than returning the prose
+    let selected: Vec<&CodeBlock> = if selected.is_empty() {
+        blocks.iter().collect()
+    } else {
+        selected
+    };
+
+    let code: Vec<&str> = selected
+        .iter()
+        .map(|block: &&CodeBlock| block.code.trim_matches('\n'))
+        .collect();
+    let mut joined: String = code.join("\n\n");
+    joined.push('\n');
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_extract_code_drops_fences_and_prose() {
+        let response: &str = "Here is the server:\n\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\nRun it with cargo run.";
+        assert_eq!(
+            extract_code(response, &RUST_TAGS),
+            "fn main() {\n    println!(\"hi\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn tests_extract_code_prefers_language_tag() {
+        let response: &str = "```toml\n[dependencies]\nactix-web = \"4\"\n```\n```rs\nuse actix_web::App;\n```\n```rust\nfn main() {}\n```";
+        assert_eq!(
+            extract_code(response, &RUST_TAGS),
+            "use actix_web::App;\n\nfn main() {}\n"
+        );
+
+        // Untagged blocks are used when no block has the language tag
+        let untagged: &str = "~~~\n[{\"route\": \"/time\"}]\n~~~";
+        assert_eq!(
+            extract_code(untagged, &JSON_TAGS),
+            "[{\"route\": \"/time\"}]\n"
+        );
+    }
+
+    #[test]
+    fn tests_extract_code_without_fences_or_closing_fence() {
+        assert_eq!(
+            extract_code("  fn main() {}\n\n", &RUST_TAGS),
+            "fn main() {}\n"
+        );
+        assert_eq!(
+            extract_code("```rust\nfn main() {}\n", &RUST_TAGS),
+            "fn main() {}\n"
+        );
+    }
+}
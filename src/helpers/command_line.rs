@@ -10,6 +10,7 @@ pub enum PrintCommand {
     AICall,
     UnitTest,
     Issue,
+    Plan,
 }
 
 impl PrintCommand {
@@ -21,6 +22,7 @@ impl PrintCommand {
             Self::AICall => Color::Cyan,
             Self::UnitTest => Color::Magenta,
             Self::Issue => Color::Red,
+            Self::Plan => Color::Yellow,
         };
 
         // Print the agent statement in a specific color
@@ -41,7 +43,7 @@ pub fn get_user_response(question: &str) -> String {
 
     // Print the question in a specific color
     stdout.execute(SetForegroundColor(Color::Blue)).unwrap();
-    println!("");
+    println!();
     println!("{}", question);
 
     // Reset Color
@@ -54,7 +56,7 @@ pub fn get_user_response(question: &str) -> String {
         .expect("Failed to read response");
 
     // Trim whitespace and return
-    return user_response.trim().to_string();
+    user_response.trim().to_string()
 }
 
 // Get user response that code is safe to execute
@@ -63,7 +65,7 @@ pub fn confirm_safe_code() -> bool {
     loop {
         // Print the question in specified color
         stdout.execute(SetForegroundColor(Color::Blue)).unwrap();
-        println!("");
+        println!();
         print!("WARNING: You are about to run code written entirely by AI. ");
         println!("Review your code and confirm you wish to continue.");
 
@@ -0,0 +1,446 @@
+use crate::apis::retry::RetryPolicy;
+use crate::helpers::approval::ApprovalPolicy;
+use crate::models::general::error::WizError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = "wizai.toml";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    // Every other path is relative to the workspace root
+    // A relative root is resolved against the directory holding wizai.toml
+    pub root: PathBuf,
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("."),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PathsConfig {
+    pub code_template: PathBuf,
+    pub web_server_project: PathBuf,
+    pub exec_main: PathBuf,
+    pub api_schema: PathBuf,
+    // OpenAPI 3.1 document of the API schema
+    pub openapi: PathBuf,
+    // Templates of the backend layers, only used when the project scope needs them
+    pub persistence_template: PathBuf,
+    pub persistence_module: PathBuf,
+    pub auth_template: PathBuf,
+    pub auth_module: PathBuf,
+    pub frontend_template: PathBuf,
+    pub frontend_output: PathBuf,
+    pub contract_template: PathBuf,
+    // Generated Solidity sources, compiled artifacts go to its build sub directory
+    pub contracts_dir: PathBuf,
+    // Generated ethers-rs modules wiring the web server to the contracts
+    pub blockchain_dir: PathBuf,
+    // Contract ABIs the blockchain modules load, inside the web server project
+    pub abi_dir: PathBuf,
+    // Factsheet and agent states of the last run
+    pub checkpoint: PathBuf,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            code_template: PathBuf::from("server-template/src/code_template.rs"),
+            web_server_project: PathBuf::from("server-template"),
+            exec_main: PathBuf::from("server-template/src/main.rs"),
+            api_schema: PathBuf::from("schemas/api_schema.json"),
+            openapi: PathBuf::from("schemas/openapi.json"),
+            persistence_template: PathBuf::from("server-template/templates/persistence.rs"),
+            persistence_module: PathBuf::from("server-template/src/persistence.rs"),
+            auth_template: PathBuf::from("server-template/templates/auth.rs"),
+            auth_module: PathBuf::from("server-template/src/auth.rs"),
+            frontend_template: PathBuf::from("server-template/src/code_template.html"),
+            frontend_output: PathBuf::from("server-template/src/output.html"),
+            contract_template: PathBuf::from("server-template/contracts/code_template.sol"),
+            contracts_dir: PathBuf::from("server-template/contracts"),
+            blockchain_dir: PathBuf::from("server-template/src/blockchain"),
+            abi_dir: PathBuf::from("server-template/abi"),
+            checkpoint: PathBuf::from("checkpoint.json"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LlmConfig {
+    // Falls back to OPEN_AI_MODEL, then the provider default
+    pub model: Option<String>,
+    pub temperature: f32,
+    pub max_attempts: u32,
+    // Model per agent position, i.e. "Backend Developer" = "gpt-4o"
+    pub agent_models: HashMap<String, String>,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            model: None,
+            temperature: 0.1,
+            max_attempts: RetryPolicy::default().max_attempts,
+            agent_models: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub port: u16,
+    // Fix attempts for endpoints which fail while the server runs
+    pub runtime_fix_attempts: u8,
+    // How long `cargo run` may take until the port accepts connections
+    pub startup_timeout_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            runtime_fix_attempts: 2,
+            startup_timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    // always-ask, auto-approve-in-sandbox or deny
+    pub policy: ApprovalPolicy,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ContractsConfig {
+    // Solidity compiler binary
+    pub solc: PathBuf,
+    // Local dev chain (anvil or a ganache compatible binary) contracts are deployed to for testing
+    pub dev_chain: PathBuf,
+    pub dev_chain_port: u16,
+    // Extra arguments for the dev chain, i.e. the mnemonic for ganache
+    pub dev_chain_args: Vec<String>,
+}
+
+impl Default for ContractsConfig {
+    fn default() -> Self {
+        Self {
+            solc: PathBuf::from("solc"),
+            dev_chain: PathBuf::from("anvil"),
+            dev_chain_port: 8545,
+            dev_chain_args: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SandboxConfig {
+    // Build and run generated code in a disposable copy of the web server project
+    pub enabled: bool,
+    // The build has no network unless allowed, dependencies are fetched before
+    pub allow_network: bool,
+    // Limits of every process in the sandbox
+    pub cpu_seconds: u64,
+    pub memory_mb: u64,
+    pub file_size_mb: u64,
+    // bubblewrap binary, Linux namespaces are used when it is not installed
+    pub bwrap: PathBuf,
+    // Environment variables passed into the sandbox, secrets are stripped regardless
+    pub env_allowlist: Vec<String>,
+    // Build output shared between sandboxes so each build is not a cold one
+    pub target_dir: PathBuf,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_network: false,
+            cpu_seconds: 1800,
+            memory_mb: 8192,
+            file_size_mb: 1024,
+            bwrap: PathBuf::from("bwrap"),
+            env_allowlist: [
+                "PATH",
+                "HOME",
+                "USER",
+                "LANG",
+                "TERM",
+                "CARGO_HOME",
+                "RUSTUP_HOME",
+                "RUSTUP_TOOLCHAIN",
+                "RUST_BACKTRACE",
+            ]
+            .iter()
+            .map(|name: &&str| name.to_string())
+            .collect(),
+            target_dir: PathBuf::from(".wizai/sandbox-target"),
+        }
+    }
+}
+
+// Contents of wizai.toml
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct WizConfig {
+    pub workspace: WorkspaceConfig,
+    pub paths: PathsConfig,
+    pub llm: LlmConfig,
+    pub server: ServerConfig,
+    pub approval: ApprovalConfig,
+    pub contracts: ContractsConfig,
+    pub sandbox: SandboxConfig,
+}
+
+impl WizConfig {
+    pub fn parse(contents: &str, base_dir: &Path) -> Result<Self, WizError> {
+        let mut config: WizConfig =
+            toml::from_str(contents).map_err(|e: toml::de::Error| WizError::Config {
+                message: e.to_string(),
+            })?;
+
+        // Anchor the workspace to the config file location
+        if config.workspace.root.is_relative() {
+            config.workspace.root = base_dir.join(&config.workspace.root);
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, WizError> {
+        let contents: String =
+            fs::read_to_string(path).map_err(|e| WizError::file_io(path.display(), e))?;
+        let base_dir: &Path = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&contents, base_dir)
+    }
+
+    // Find wizai.toml in `start` or any of its parent directories
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir: &Path| dir.join(CONFIG_FILE_NAME))
+            .find(|candidate: &PathBuf| candidate.is_file())
+    }
+
+    // Load the given config file, or discover one from the working directory.
+    // Without a config file the defaults are used with the working directory as workspace root.
+    pub fn resolve(explicit: Option<&Path>) -> Result<Self, WizError> {
+        let cwd: PathBuf = env::current_dir().map_err(|e| WizError::file_io(".", e))?;
+
+        match explicit {
+            Some(path) => Self::load(path),
+            None => match Self::discover(&cwd) {
+                Some(path) => Self::load(&path),
+                None => Ok(Self::with_root(&cwd)),
+            },
+        }
+    }
+
+    pub fn with_root(root: &Path) -> Self {
+        Self {
+            workspace: WorkspaceConfig {
+                root: root.to_path_buf(),
+            },
+            ..Self::default()
+        }
+    }
+
+    // Generate into another directory, still reading the code template from the current workspace
+    pub fn with_output_dir(mut self, output_dir: &Path) -> Self {
+        self.paths.code_template = self.code_template_path();
+        self.paths.persistence_template = self.persistence_template_path();
+        self.paths.auth_template = self.auth_template_path();
+        self.paths.frontend_template = self.frontend_template_path();
+        self.paths.contract_template = self.contract_template_path();
+        self.workspace.root = output_dir.to_path_buf();
+        self
+    }
+
+    pub fn workspace_path(&self, relative: &Path) -> PathBuf {
+        self.workspace.root.join(relative)
+    }
+
+    pub fn code_template_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.code_template)
+    }
+
+    pub fn web_server_project_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.web_server_project)
+    }
+
+    pub fn exec_main_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.exec_main)
+    }
+
+    pub fn api_schema_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.api_schema)
+    }
+
+    pub fn openapi_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.openapi)
+    }
+
+    pub fn persistence_template_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.persistence_template)
+    }
+
+    pub fn persistence_module_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.persistence_module)
+    }
+
+    pub fn auth_template_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.auth_template)
+    }
+
+    pub fn auth_module_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.auth_module)
+    }
+
+    pub fn frontend_template_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.frontend_template)
+    }
+
+    pub fn frontend_output_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.frontend_output)
+    }
+
+    pub fn contract_template_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.contract_template)
+    }
+
+    pub fn contracts_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.contracts_dir)
+    }
+
+    pub fn contracts_build_path(&self) -> PathBuf {
+        self.contracts_path().join("build")
+    }
+
+    pub fn blockchain_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.blockchain_dir)
+    }
+
+    pub fn abi_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.abi_dir)
+    }
+
+    pub fn sandbox_target_path(&self) -> PathBuf {
+        self.workspace_path(&self.sandbox.target_dir)
+    }
+
+    pub fn checkpoint_path(&self) -> PathBuf {
+        self.workspace_path(&self.paths.checkpoint)
+    }
+
+    pub fn server_url(&self, route: &str) -> String {
+        format!("http://localhost:{}{}", self.server.port, route)
+    }
+
+    pub fn dev_chain_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.contracts.dev_chain_port)
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default().with_max_attempts(self.llm.max_attempts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_parse_config_with_defaults() {
+        let contents: &str = r#"
+            [workspace]
+            root = "projects/demo"
+
+            [paths]
+            exec_main = "server/src/main.rs"
+
+            [llm]
+            model = "gpt-4o"
+            agent_models = { "Backend Developer" = "gpt-4.1" }
+
+            [server]
+            port = 9090
+
+            [approval]
+            policy = "auto-approve-in-sandbox"
+        "#;
+
+        let config: WizConfig = WizConfig::parse(contents, Path::new("/home/dev")).unwrap();
+
+        assert_eq!(
+            config.exec_main_path(),
+            PathBuf::from("/home/dev/projects/demo/server/src/main.rs")
+        );
+        assert_eq!(
+            config.code_template_path(),
+            PathBuf::from("/home/dev/projects/demo/server-template/src/code_template.rs")
+        );
+        assert_eq!(config.llm.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(config.llm.temperature, 0.1);
+        assert_eq!(config.llm.agent_models["Backend Developer"], "gpt-4.1");
+        assert_eq!(config.server_url("/time"), "http://localhost:9090/time");
+        assert_eq!(config.server.runtime_fix_attempts, 2);
+        assert_eq!(config.server.startup_timeout_secs, 30);
+        assert_eq!(config.approval.policy, ApprovalPolicy::AutoApproveInSandbox);
+    }
+
+    #[test]
+    fn tests_output_dir_keeps_code_template() {
+        let config: WizConfig = WizConfig::with_root(Path::new("/home/dev/wizai"))
+            .with_output_dir(Path::new("/tmp/out"));
+
+        assert_eq!(
+            config.code_template_path(),
+            PathBuf::from("/home/dev/wizai/server-template/src/code_template.rs")
+        );
+        assert_eq!(
+            config.exec_main_path(),
+            PathBuf::from("/tmp/out/server-template/src/main.rs")
+        );
+        assert_eq!(
+            config.checkpoint_path(),
+            PathBuf::from("/tmp/out/checkpoint.json")
+        );
+    }
+
+    #[test]
+    fn tests_invalid_config_is_reported() {
+        let res: Result<WizConfig, WizError> =
+            WizConfig::parse("[server]\nport = \"eighty\"", Path::new("."));
+        assert!(matches!(res, Err(WizError::Config { .. })));
+    }
+
+    #[test]
+    fn tests_discover_config_in_parent_directory() {
+        let root: PathBuf =
+            env::temp_dir().join(format!("wizai_config_{}_discover", std::process::id()));
+        let nested: PathBuf = root.join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(CONFIG_FILE_NAME), "[server]\nport = 3000\n").unwrap();
+
+        let found: PathBuf = WizConfig::discover(&nested).expect("wizai.toml not found");
+        let config: WizConfig = WizConfig::load(&found).unwrap();
+        assert_eq!(config.server.port, 3000);
+        assert_eq!(config.workspace.root, root.join("."));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
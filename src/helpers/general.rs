@@ -1,17 +1,21 @@
-use crate::apis::call_request::call_gpt;
+use crate::apis::llm_provider::{LlmProvider, LlmRequest};
 use crate::helpers::command_line::PrintCommand;
+use crate::helpers::config::WizConfig;
+use crate::helpers::json_decode::{decode_response, json_schema, DecodeFailure};
+use crate::models::agents::agent_traits::RouteObject;
+use crate::models::agents_manager::checkpoint::Checkpoint;
+use crate::models::general::error::WizError;
 use crate::models::general::llm::MessageAI;
 use reqwest::Client;
+use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-pub const CODE_TEMPLATE_PATH: &str =
-    "/Users/kwamebryan/RustroverProjects/DAppWiz/server-template/src/code_template.rs";
-
-pub const WEB_SERVER_PROJECT_PATH: &str = "/Users/kwamebryan/RustroverProjects/DAppWiz/server-template/";
-pub const EXEC_MAIN_PATH: &str =
-    "/Users/kwamebryan/RustroverProjects/DAppWiz/server-template/src/main.rs";
-pub const API_SCHEMA_PATH: &str = "/Users/kwamebryan/RustroverProjects/DAppWiz/schemas/api_schema.json";
+// Times an invalid JSON response is sent back to the model before decoding fails
+pub const DECODE_REPAIR_ATTEMPTS: u8 = 2;
 
 // Extend ai function to encourage specific output
 pub fn extend_ai_function(ai_func: fn(&str) -> &'static str, func_input: &str) -> MessageAI {
@@ -33,13 +37,14 @@ pub fn extend_ai_function(ai_func: fn(&str) -> &'static str, func_input: &str) -
     }
 }
 
-// Performs call to LLM GPT
+// Performs call to LLM
 pub async fn ai_task_request(
+    provider: &dyn LlmProvider,
     msg_context: String,
     agent_position: &str,
     agent_operation: &str,
     function_pass: for<'a> fn(&'a str) -> &'static str,
-) -> String {
+) -> Result<String, WizError> {
     // Extend AI function
     let extended_msg: MessageAI = extend_ai_function(function_pass, &msg_context);
 
@@ -47,30 +52,89 @@ pub async fn ai_task_request(
     PrintCommand::AICall.print_agent_message(agent_position, agent_operation);
 
     // Get LLM response
-    let llm_response_res: Result<String, Box<dyn std::error::Error + Send>> =
-        call_gpt(vec![extended_msg.clone()]).await;
-
-    // Return Success or try again
-    match llm_response_res {
-        Ok(llm_resp) => llm_resp,
-        Err(_) => call_gpt(vec![extended_msg.clone()])
-            .await
-            .expect("Failed twice to call OpenAI"),
-    }
+    let request: LlmRequest = LlmRequest {
+        function_name: agent_operation.to_string(),
+        msg_context,
+        messages: vec![extended_msg],
+    };
+    // Retries are handled by the provider (see apis::retry::RetryProvider)
+    provider.complete(&request).await
 }
 
-// Performs call to LLM GPT - Decoded
-pub async fn ai_task_request_decoded<T: DeserializeOwned>(
+// Performs call to LLM - Decoded
+// Responses which do not match the JSON Schema of T are sent back to the model with the
+// validation errors, up to DECODE_REPAIR_ATTEMPTS times
+pub async fn ai_task_request_decoded<T: DeserializeOwned + JsonSchema>(
+    provider: &dyn LlmProvider,
     msg_context: String,
     agent_position: &str,
     agent_operation: &str,
     function_pass: for<'a> fn(&'a str) -> &'static str,
-) -> T {
-    let llm_response: String =
-        ai_task_request(msg_context, agent_position, agent_operation, function_pass).await;
-    let decoded_response: T = serde_json::from_str(llm_response.as_str())
-        .expect("Failed to decode ai response from serde_json");
-    return decoded_response;
+) -> Result<T, WizError> {
+    let mut llm_response: String = ai_task_request(
+        provider,
+        msg_context.clone(),
+        agent_position,
+        agent_operation,
+        function_pass,
+    )
+    .await?;
+
+    let mut repair_count: u8 = 0;
+    loop {
+        let failure: DecodeFailure = match decode_response::<T>(&llm_response) {
+            Ok(decoded) => return Ok(decoded),
+            Err(failure) => failure,
+        };
+        if repair_count >= DECODE_REPAIR_ATTEMPTS {
+            return Err(WizError::Decode {
+                function_name: agent_operation.to_string(),
+                message: failure.errors.join("; "),
+            });
+        }
+        repair_count += 1;
+
+        PrintCommand::Issue.print_agent_message(
+            agent_position,
+            &format!(
+                "Response of {} is invalid, asking for a repair ({}/{})",
+                agent_operation, repair_count, DECODE_REPAIR_ATTEMPTS
+            ),
+        );
+        let repair_msg: MessageAI = MessageAI {
+            role: "user".to_string(),
+            content: format!(
+                "Your output is not valid for this JSON Schema: {}
+  VALIDATION ERRORS: {}
+  Print out the corrected JSON only. Nothing else. No commentary.",
+                json_schema::<T>(),
+                failure.errors.join("\n")
+            ),
+        };
+        let request: LlmRequest = LlmRequest {
+            function_name: agent_operation.to_string(),
+            msg_context: msg_context.clone(),
+            messages: vec![
+                extend_ai_function(function_pass, &msg_context),
+                MessageAI {
+                    role: "assistant".to_string(),
+                    content: llm_response,
+                },
+                repair_msg,
+            ],
+        };
+        llm_response = provider.complete(&request).await?;
+    }
+}
+
+// HTTP client with a request timeout, for testing URLs and endpoints
+pub fn http_client(timeout: Duration) -> Result<Client, WizError> {
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e: reqwest::Error| WizError::HttpClient {
+            message: e.to_string(),
+        })
 }
 
 // Check whether request url is valid
@@ -80,33 +144,195 @@ pub async fn check_status_code(client: &Client, url: &str) -> Result<u16, reqwes
 }
 
 // Get Code Template
-pub fn read_code_template_contents() -> String {
-    let path: String = String::from(CODE_TEMPLATE_PATH);
-    fs::read_to_string(path).expect("Failed to read code template")
+pub fn read_code_template_contents(config: &WizConfig) -> Result<String, WizError> {
+    read_file(&config.code_template_path())
 }
 
 // Get Exec Main
-pub fn read_exec_main_contents() -> String {
-    let path: String = String::from(EXEC_MAIN_PATH);
-    fs::read_to_string(path).expect("Failed to read code template")
+pub fn read_exec_main_contents(config: &WizConfig) -> Result<String, WizError> {
+    read_file(&config.exec_main_path())
 }
 
 // Save New Backend Code
-pub fn save_backend_code(contents: &String) {
-    let path: String = String::from(EXEC_MAIN_PATH);
-    fs::write(path, contents).expect("Failed to write main.rs file");
+pub fn save_backend_code(config: &WizConfig, contents: &str) -> Result<(), WizError> {
+    write_file(&config.exec_main_path(), contents)
+}
+
+// Get Persistence Layer Code Template
+pub fn read_persistence_template_contents(config: &WizConfig) -> Result<String, WizError> {
+    read_file(&config.persistence_template_path())
+}
+
+// Save New Persistence Layer Code
+pub fn save_persistence_code(config: &WizConfig, contents: &str) -> Result<(), WizError> {
+    write_file(&config.persistence_module_path(), contents)
+}
+
+// Get Auth Layer Code Template
+pub fn read_auth_template_contents(config: &WizConfig) -> Result<String, WizError> {
+    read_file(&config.auth_template_path())
+}
+
+// Save New Auth Layer Code
+pub fn save_auth_code(config: &WizConfig, contents: &str) -> Result<(), WizError> {
+    write_file(&config.auth_module_path(), contents)
+}
+
+// Rust code with `mod <module_name>;` declared at the top, None if it already is
+pub fn declare_module(code: &str, module_name: &str) -> Option<String> {
+    let declaration: String = format!("mod {};", module_name);
+    let is_declared: bool = code.lines().any(|line: &str| {
+        let line: &str = line.trim();
+        line == declaration || line == format!("pub {}", declaration)
+    });
+    if is_declared {
+        return None;
+    }
+    Some(format!("#[allow(dead_code)]\n{}\n{}", declaration, code))
+}
+
+// Get Frontend Code Template
+pub fn read_frontend_template_contents(config: &WizConfig) -> Result<String, WizError> {
+    read_file(&config.frontend_template_path())
+}
+
+// Save New Frontend Code
+pub fn save_frontend_code(config: &WizConfig, contents: &str) -> Result<(), WizError> {
+    write_file(&config.frontend_output_path(), contents)
+}
+
+// Get Smart Contract Code Template
+pub fn read_contract_template_contents(config: &WizConfig) -> Result<String, WizError> {
+    read_file(&config.contract_template_path())
+}
+
+// Save New Smart Contract Code, returns the path of the Solidity source
+pub fn save_contract_code(
+    config: &WizConfig,
+    contract_name: &str,
+    contents: &str,
+) -> Result<PathBuf, WizError> {
+    let path: PathBuf = config
+        .contracts_path()
+        .join(format!("{}.sol", contract_name));
+    write_file(&path, contents)?;
+    Ok(path)
+}
+
+// Save a generated blockchain integration module, returns the path of the Rust source
+pub fn save_blockchain_module(
+    config: &WizConfig,
+    module_name: &str,
+    contents: &str,
+) -> Result<PathBuf, WizError> {
+    let path: PathBuf = config.blockchain_path().join(format!("{}.rs", module_name));
+    write_file(&path, contents)?;
+    Ok(path)
+}
+
+// Save the ABI of a compiled contract for the web server, returns the path of the JSON file
+pub fn save_contract_abi(
+    config: &WizConfig,
+    contract_name: &str,
+    abi: &serde_json::Value,
+) -> Result<PathBuf, WizError> {
+    let path: PathBuf = config.abi_path().join(format!("{}.json", contract_name));
+    let contents: String =
+        serde_json::to_string_pretty(abi).expect("ABI is always serializable to JSON");
+    write_file(&path, &contents)?;
+    Ok(path)
 }
 
 // Save JSON API Endpoint Schema
-pub fn save_api_endpoints(api_endpoints: &String) {
-    let path: String = String::from(API_SCHEMA_PATH);
-    fs::write(path, api_endpoints).expect("Failed to write API Endpoints to file");
+pub fn save_api_endpoints(config: &WizConfig, api_endpoints: &str) -> Result<(), WizError> {
+    write_file(&config.api_schema_path(), api_endpoints)
+}
+
+// Save OpenAPI Document of the API Endpoint Schema
+pub fn save_openapi_document(config: &WizConfig, document: &str) -> Result<(), WizError> {
+    write_file(&config.openapi_path(), document)
+}
+
+// Get saved JSON API Endpoint Schema
+pub fn read_api_endpoints(config: &WizConfig) -> Result<Vec<RouteObject>, WizError> {
+    read_json_file(&config.api_schema_path())
+}
+
+// Load the checkpoint saved by a previous run
+pub fn read_checkpoint(config: &WizConfig) -> Result<Checkpoint, WizError> {
+    read_json_file(&config.checkpoint_path())
+}
+
+// Save the checkpoint so later commands (i.e. build or --resume) can pick it up
+pub fn save_checkpoint(config: &WizConfig, checkpoint: &Checkpoint) -> Result<(), WizError> {
+    let contents: String = serde_json::to_string_pretty(checkpoint)
+        .expect("Checkpoint is always serializable to JSON");
+    write_file(&config.checkpoint_path(), &contents)
+}
+
+// Copy the server project template (without build output) into a new location
+pub fn copy_project_template(from: &Path, to: &Path) -> Result<(), WizError> {
+    copy_project_excluding(from, to, &[])
+}
+
+// Like copy_project_template, without the files and directories in `excluded`
+pub fn copy_project_excluding(
+    from: &Path,
+    to: &Path,
+    excluded: &[PathBuf],
+) -> Result<(), WizError> {
+    fs::create_dir_all(to).map_err(|e| WizError::file_io(to.display(), e))?;
+    let entries: fs::ReadDir =
+        fs::read_dir(from).map_err(|e| WizError::file_io(from.display(), e))?;
+
+    for entry in entries {
+        let entry: fs::DirEntry = entry.map_err(|e| WizError::file_io(from.display(), e))?;
+        let source: PathBuf = entry.path();
+        let target: PathBuf = to.join(entry.file_name());
+        if excluded.contains(&source) {
+            continue;
+        }
+
+        if source.is_dir() {
+            if entry.file_name() != "target" {
+                copy_project_excluding(&source, &target, excluded)?;
+            }
+        } else {
+            fs::copy(&source, &target).map_err(|e| WizError::file_io(source.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn read_file(path: &Path) -> Result<String, WizError> {
+    fs::read_to_string(path).map_err(|e| WizError::file_io(path.display(), e))
+}
+
+fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T, WizError> {
+    let contents: String = read_file(path)?;
+    serde_json::from_str(&contents).map_err(|e: serde_json::Error| {
+        WizError::file_io(
+            path.display(),
+            io::Error::new(io::ErrorKind::InvalidData, e),
+        )
+    })
+}
+
+// Writes the file, creating missing parent directories
+fn write_file(path: &Path, contents: &str) -> Result<(), WizError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| WizError::file_io(parent.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| WizError::file_io(path.display(), e))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::ai_functions::ai_func_managing::convert_user_input_to_goal;
+    use crate::ai_functions::aifunc_architect::print_project_scope;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::models::agents::agent_traits::ProjectScope;
 
     #[test]
     fn tests_extending_ai_function() {
@@ -121,14 +347,107 @@ mod tests {
         let ai_func_param: String =
             "Build me a webserver for making stock price api requests.".to_string();
 
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+
         let res: String = ai_task_request(
+            &provider,
             ai_func_param,
             "Managing Agent",
-            "Defining user requirements",
+            get_function_string!(convert_user_input_to_goal),
             convert_user_input_to_goal,
         )
-            .await;
+        .await
+        .expect("Failed to call LLM provider");
 
         assert!(res.len() > 20);
     }
+
+    #[tokio::test]
+    async fn tests_ai_task_request_decoded_reports_bad_json() {
+        let provider: MockProvider =
+            MockProvider::new().with_response("print_project_scope", "{ not json");
+
+        let res: Result<ProjectScope, WizError> = ai_task_request_decoded::<ProjectScope>(
+            &provider,
+            "a todo app".to_string(),
+            "Solutions Architect",
+            get_function_string!(print_project_scope),
+            print_project_scope,
+        )
+        .await;
+
+        assert!(matches!(res, Err(WizError::Decode { .. })));
+        assert_eq!(provider.calls().len(), 1 + DECODE_REPAIR_ATTEMPTS as usize);
+    }
+
+    #[tokio::test]
+    async fn tests_ai_task_request_decoded_repairs_invalid_json() {
+        let provider: MockProvider = MockProvider::new()
+            .with_response(
+                "print_project_scope",
+                "{\"is_crud_required\": bool true, \"is_user_login_and_logout\": false}",
+            )
+            .with_response(
+                "print_project_scope",
+                "{\"is_crud_required\": true, \"is_user_login_and_logout\": false}",
+            )
+            .with_response(
+                "print_project_scope",
+                "{\"is_crud_required\": true, \"is_user_login_and_logout\": false, \"is_external_urls_required\": false}",
+            );
+
+        let scope: ProjectScope = ai_task_request_decoded::<ProjectScope>(
+            &provider,
+            "a todo app".to_string(),
+            "Solutions Architect",
+            get_function_string!(print_project_scope),
+            print_project_scope,
+        )
+        .await
+        .unwrap();
+
+        assert!(scope.is_crud_required);
+        assert_eq!(provider.calls().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn tests_ai_task_request_decoded_strips_code_fences() {
+        let provider: MockProvider = MockProvider::new().with_response(
+            "print_project_scope",
+            "The scope is:\n```json\n{\"is_crud_required\": true, \"is_user_login_and_logout\": false, \"is_external_urls_required\": false}\n```",
+        );
+
+        let scope: ProjectScope = ai_task_request_decoded::<ProjectScope>(
+            &provider,
+            "a todo app".to_string(),
+            "Solutions Architect",
+            get_function_string!(print_project_scope),
+            print_project_scope,
+        )
+        .await
+        .unwrap();
+
+        assert!(scope.is_crud_required);
+    }
+
+    #[test]
+    fn tests_copy_project_template_skips_build_output() {
+        let root: PathBuf =
+            std::env::temp_dir().join(format!("wizai_general_{}_copy", std::process::id()));
+        let from: PathBuf = root.join("template");
+        fs::create_dir_all(from.join("src")).unwrap();
+        fs::create_dir_all(from.join("target/debug")).unwrap();
+        fs::write(from.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(from.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let to: PathBuf = root.join("out/server");
+        copy_project_template(&from, &to).unwrap();
+
+        assert!(to.join("Cargo.toml").is_file());
+        assert!(to.join("src/main.rs").is_file());
+        assert!(!to.join("target").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,155 @@
+use crate::helpers::code_fence::{extract_code, JSON_TAGS};
+
+use jsonschema::JSONSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+// Why a response could not be decoded, sent back to the model for repair
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeFailure {
+    pub json: String,
+    pub errors: Vec<String>,
+}
+
+// Decodes the JSON of an LLM response into T
+// The JSON is taken from code fences or the surrounding prose, cleaned of comments and trailing
+// commas and validated against the JSON Schema of T before it is deserialized
+pub fn decode_response<T: DeserializeOwned + JsonSchema>(
+    response: &str,
+) -> Result<T, DecodeFailure> {
+    let json: String = clean_json(&extract_json(response));
+    let fail = |errors: Vec<String>| DecodeFailure {
+        json: json.clone(),
+        errors,
+    };
+
+    let value: Value = serde_json::from_str(&json).map_err(|e| fail(vec![e.to_string()]))?;
+    validate_schema::<T>(&value).map_err(fail)?;
+    serde_json::from_value(value).map_err(|e| fail(vec![e.to_string()]))
+}
+
+// JSON Schema derived from T
+pub fn json_schema<T: JsonSchema>() -> Value {
+    serde_json::to_value(schema_for!(T)).expect("JSON Schema is always serializable to JSON")
+}
+
+pub fn validate_schema<T: JsonSchema>(value: &Value) -> Result<(), Vec<String>> {
+    let schema: Value = json_schema::<T>();
+    let compiled: JSONSchema =
+        JSONSchema::compile(&schema).map_err(|e| vec![format!("invalid schema: {}", e)])?;
+
+    compiled.validate(value).map_err(|errors| {
+        errors
+            .map(|error| {
+                let path: String = error.instance_path.to_string();
+                if path.is_empty() {
+                    error.to_string()
+                } else {
+                    format!("{}: {}", path, error)
+                }
+            })
+            .collect()
+    })
+}
+
+// The JSON of a response: fenced json or untagged blocks first, otherwise the outermost
+// object or array within the prose
+pub fn extract_json(response: &str) -> String {
+    let code: String = extract_code(response, &JSON_TAGS);
+    let trimmed: &str = code.trim();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return trimmed.to_string();
+    }
+
+    let start: Option<usize> = trimmed.find(['{', '[']);
+    let end: Option<usize> = trimmed.rfind(['}', ']']);
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => trimmed[start..=end].to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+// Drops // comments and trailing commas outside of strings
+fn clean_json(json: &str) -> String {
+    let mut cleaned: String = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string: bool = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            cleaned.push(c);
+            match c {
+                '\\' => cleaned.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                cleaned.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|c: &char| *c != '\n') {
+                    chars.next();
+                }
+            }
+            '}' | ']' => {
+                let kept: usize = cleaned.trim_end().len();
+                if cleaned[..kept].ends_with(',') {
+                    cleaned.truncate(kept - 1);
+                }
+                cleaned.push(c);
+            }
+            _ => cleaned.push(c),
+        }
+    }
+    cleaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::agents::agent_traits::ProjectScope;
+
+    #[test]
+    fn tests_decode_prose_wrapped_json() {
+        let response: &str = r#"Sure, here is the scope:
+{
+  "is_crud_required": true, // todos are stored
+  "is_user_login_and_logout": false,
+  "is_external_urls_required": false,
+}
+Let me know if you need anything else."#;
+
+        let scope: ProjectScope = decode_response(response).unwrap();
+        assert!(scope.is_crud_required);
+        assert!(!scope.is_blockchain_required);
+
+        let urls: Vec<String> =
+            decode_response("```json\n[\"https://example.com\",]\n```").unwrap();
+        assert_eq!(urls, vec!["https://example.com".to_string()]);
+    }
+
+    #[test]
+    fn tests_decode_reports_schema_errors() {
+        let failure: DecodeFailure = decode_response::<ProjectScope>(
+            r#"{"is_crud_required": "yes", "is_user_login_and_logout": false}"#,
+        )
+        .unwrap_err();
+
+        assert!(failure.errors.iter().any(|error: &String| error
+            .starts_with("/is_crud_required: \"yes\" is not of type \"boolean\"")));
+        assert!(failure
+            .errors
+            .iter()
+            .any(|error: &String| error
+                .contains("\"is_external_urls_required\" is a required property")));
+
+        // A comma inside a string is not a trailing comma
+        assert_eq!(clean_json(r#"["a,]", ]"#), r#"["a,]"]"#);
+    }
+}
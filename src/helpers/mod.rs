@@ -1,2 +1,15 @@
+pub mod approval;
+pub mod code_fence;
 pub mod command_line;
+pub mod config;
 pub mod general;
+pub mod json_decode;
+pub mod openapi;
+pub mod process_output;
+pub mod request_synthesizer;
+pub mod route_extractor;
+pub mod sandbox;
+pub mod server_probe;
+pub mod server_process;
+#[cfg(test)]
+pub mod test_support;
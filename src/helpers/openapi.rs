@@ -0,0 +1,427 @@
+use crate::models::agents::agent_traits::RouteObject;
+use crate::models::general::route::{path_parameter, route_key, FieldType};
+
+use jsonschema::{Draft, JSONSchema};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+const OPENAPI_TITLE: &str = "WizAI generated API";
+const API_VERSION: &str = "0.1.0";
+
+// Fields of a path item which are operations
+const OPERATION_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+// Official JSON Schema of OpenAPI 3.1 documents, https://spec.openapis.org/oas/3.1/schema/2022-10-07
+const OAS_SCHEMA: &str = include_str!("oas-3.1-schema.json");
+
+// OpenAPI 3.1 document of the endpoints, served from `server_url`
+// Routes which only differ in their parameter names share one path, as OpenAPI requires
+pub fn openapi_document(endpoints: &[RouteObject], server_url: &str) -> Value {
+    let mut paths: Map<String, Value> = Map::new();
+    let mut templates: HashMap<String, String> = HashMap::new();
+    let mut operation_ids: HashSet<String> = HashSet::new();
+
+    for endpoint in endpoints {
+        let path: String = templates
+            .entry(route_key(&endpoint.route))
+            .or_insert_with(|| openapi_path(&endpoint.route))
+            .clone();
+
+        // Parameters keep the types of this route and take the names of the shared path
+        let parameters: Vec<Value> = endpoint
+            .route
+            .split('/')
+            .filter_map(path_parameter_pattern)
+            .zip(path.split('/').filter_map(path_parameter))
+            .map(|((name, pattern), template_name)| {
+                path_parameter_object(template_name, endpoint.path_types.get(name), pattern)
+            })
+            .collect();
+
+        let mut operation: Map<String, Value> = Map::new();
+        operation.insert(
+            "operationId".to_string(),
+            json!(operation_id(endpoint, &mut operation_ids)),
+        );
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_string(), Value::Array(parameters));
+        }
+        if endpoint.request_body != FieldType::None {
+            operation.insert(
+                "requestBody".to_string(),
+                json!({
+                    "required": true,
+                    "content": {"application/json": {"schema": json_schema(&endpoint.request_body)}}
+                }),
+            );
+        }
+        let mut response: Value = json!({"description": "Successful response"});
+        if endpoint.response != FieldType::None {
+            response["content"] =
+                json!({"application/json": {"schema": json_schema(&endpoint.response)}});
+        }
+        operation.insert("responses".to_string(), json!({"200": response}));
+
+        if let Value::Object(path_item) = paths.entry(path).or_insert_with(|| json!({})) {
+            path_item.insert(
+                endpoint.method.as_str().to_string(),
+                Value::Object(operation),
+            );
+        }
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {"title": OPENAPI_TITLE, "version": API_VERSION},
+        "servers": [{"url": server_url, "description": "Generated server running locally"}],
+        "paths": paths
+    })
+}
+
+// JSON Schema (draft 2020-12, the dialect of OpenAPI 3.1) of a body
+// Every field of an object is required, like the endpoint tests expect
+pub fn json_schema(field_type: &FieldType) -> Value {
+    match field_type {
+        FieldType::None => json!({"type": "null"}),
+        FieldType::Any => json!({}),
+        FieldType::String => json!({"type": "string"}),
+        FieldType::Number => json!({"type": "number"}),
+        FieldType::Integer => json!({"type": "integer"}),
+        FieldType::Bool => json!({"type": "boolean"}),
+        FieldType::Array(item_type) => json!({"type": "array", "items": json_schema(item_type)}),
+        FieldType::Object(fields) => json!({
+            "type": "object",
+            "properties": fields
+                .iter()
+                .map(|(name, field_type)| (name.clone(), json_schema(field_type)))
+                .collect::<Map<String, Value>>(),
+            "required": fields.keys().collect::<Vec<&String>>()
+        }),
+    }
+}
+
+// Checks the document against the official OpenAPI 3.1 schema, then the rules tools rely on
+// which the schema cannot express: declared path parameters, paths which only differ in their
+// parameter names, unique operation ids and valid JSON Schemas
+pub fn validate_openapi(document: &Value) -> Result<(), Vec<String>> {
+    let mut errors: Vec<String> = validate_oas_schema(document);
+
+    let mut path_keys: HashMap<String, &String> = HashMap::new();
+    let mut operation_ids: HashSet<&str> = HashSet::new();
+    for (path, path_item) in document["paths"].as_object().into_iter().flatten() {
+        if let Some(other_path) = path_keys.insert(route_key(path), path) {
+            errors.push(format!(
+                "{} and {} only differ in their parameter names",
+                other_path, path
+            ));
+        }
+
+        let template_names: Vec<&str> = path.split('/').filter_map(path_parameter).collect();
+        for method in OPERATION_METHODS {
+            let Some(operation) = path_item.get(method) else {
+                continue;
+            };
+
+            let location: String = format!("{} {}", method.to_uppercase(), path);
+            if let Some(operation_id) = operation["operationId"].as_str() {
+                if !operation_ids.insert(operation_id) {
+                    errors.push(format!(
+                        "{}: operationId `{}` is not unique",
+                        location, operation_id
+                    ));
+                }
+            }
+            validate_operation(&location, operation, &template_names, &mut errors);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// Errors of the document against the vendored OAS 3.1 schema (2022-10-07)
+// jsonschema does not resolve `$dynamicRef`, so the schema objects within are left to
+// validate_schema
+fn validate_oas_schema(document: &Value) -> Vec<String> {
+    let oas_schema: Value =
+        serde_json::from_str(OAS_SCHEMA).expect("The vendored OAS schema is valid JSON");
+    let compiled: JSONSchema = JSONSchema::options()
+        .with_draft(Draft::Draft202012)
+        .compile(&oas_schema)
+        .expect("The vendored OAS schema compiles");
+
+    let Err(schema_errors) = compiled.validate(document) else {
+        return vec![];
+    };
+    // unevaluatedProperties reports an error once for every branch it evaluated
+    let mut errors: Vec<String> = vec![];
+    for error in schema_errors {
+        let path: String = error.instance_path.to_string();
+        let error: String = if path.is_empty() {
+            error.to_string()
+        } else {
+            format!("{}: {}", path, error)
+        };
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+    errors
+}
+
+fn validate_operation(
+    location: &str,
+    operation: &Value,
+    template_names: &[&str],
+    errors: &mut Vec<String>,
+) {
+    let parameters: &[Value] = operation["parameters"]
+        .as_array()
+        .map_or(&[], Vec::as_slice);
+    let mut declared: HashSet<(&str, &str)> = HashSet::new();
+    for parameter in parameters {
+        let (Some(name), Some(kind)) = (parameter["name"].as_str(), parameter["in"].as_str())
+        else {
+            continue;
+        };
+        if !declared.insert((name, kind)) {
+            errors.push(format!(
+                "{}: parameter `{}` is declared twice",
+                location, name
+            ));
+        }
+        if kind == "path" && !template_names.contains(&name) {
+            errors.push(format!(
+                "{}: path parameter `{}` is not in the path",
+                location, name
+            ));
+        }
+        validate_schema(location, &parameter["schema"], errors);
+    }
+    for name in template_names {
+        if !declared.contains(&(*name, "path")) {
+            errors.push(format!(
+                "{}: path parameter `{}` is not declared",
+                location, name
+            ));
+        }
+    }
+
+    let request_content: Option<&Map<String, Value>> =
+        operation["requestBody"]["content"].as_object();
+    let response_contents = operation["responses"]
+        .as_object()
+        .into_iter()
+        .flat_map(|responses: &Map<String, Value>| responses.values())
+        .filter_map(|response: &Value| response["content"].as_object());
+    for content in request_content.into_iter().chain(response_contents) {
+        for media_type in content.values() {
+            validate_schema(location, &media_type["schema"], errors);
+        }
+    }
+}
+
+fn validate_schema(location: &str, schema: &Value, errors: &mut Vec<String>) {
+    if schema.is_null() {
+        return;
+    }
+    if let Err(e) = JSONSchema::options()
+        .with_draft(Draft::Draft202012)
+        .compile(schema)
+    {
+        errors.push(format!("{}: invalid schema {}: {}", location, schema, e));
+    }
+}
+
+// /task/{id:\d+} is /task/{id} in OpenAPI
+fn openapi_path(route: &str) -> String {
+    route
+        .split('/')
+        .map(|segment: &str| match path_parameter(segment) {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+// Name and actix regex of a path parameter segment, i.e. ("id", Some("\d+")) for {id:\d+}
+fn path_parameter_pattern(segment: &str) -> Option<(&str, Option<&str>)> {
+    let name: &str = path_parameter(segment)?;
+    let pattern: Option<&str> = segment[1..segment.len() - 1]
+        .split_once(':')
+        .map(|(_, pattern)| pattern);
+    Some((name, pattern))
+}
+
+// Path segments are strings unless the handler tells their type
+fn path_parameter_object(
+    name: &str,
+    field_type: Option<&FieldType>,
+    pattern: Option<&str>,
+) -> Value {
+    let mut schema: Value = field_type.map_or(json!({"type": "string"}), json_schema);
+    if let (Some(pattern), Some("string")) = (pattern, schema["type"].as_str()) {
+        schema["pattern"] = json!(format!("^{}$", pattern));
+    }
+    json!({"name": name, "in": "path", "required": true, "schema": schema})
+}
+
+// i.e. get_task_id for GET /task/{id}, numbered when two routes give the same id
+fn operation_id(endpoint: &RouteObject, operation_ids: &mut HashSet<String>) -> String {
+    let words: Vec<String> = endpoint
+        .route
+        .split('/')
+        .map(|segment: &str| path_parameter(segment).unwrap_or(segment))
+        .map(|segment: &str| {
+            segment
+                .chars()
+                .map(|c: char| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>()
+        })
+        .filter(|word: &String| !word.trim_matches('_').is_empty())
+        .collect();
+    let base: String = [endpoint.method.as_str().to_string()]
+        .into_iter()
+        .chain(words)
+        .collect::<Vec<String>>()
+        .join("_");
+
+    let mut id: String = base.clone();
+    let mut count: u32 = 1;
+    while !operation_ids.insert(id.clone()) {
+        count += 1;
+        id = format!("{}_{}", base, count);
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_openapi_document_of_the_api_schema() {
+        let mut endpoints: Vec<RouteObject> = serde_json::from_str(include_str!(
+            "../../fixtures/llm/print_rest_api_endpoints.txt"
+        ))
+        .unwrap();
+        endpoints.extend(
+            serde_json::from_value::<Vec<RouteObject>>(json!([
+                {
+                    "route": "/workout/{workout_id}/tag/{tag:\\w+}",
+                    "method": "delete",
+                    "path_types": {"workout_id": "integer", "tag": "string"}
+                },
+                {"route": "/workout/{workout_id}", "method": "patch", "request_body": {"completed": "bool"}}
+            ]))
+            .unwrap(),
+        );
+
+        let document: Value = openapi_document(&endpoints, "http://localhost:8080");
+        assert_eq!(validate_openapi(&document), Ok(()));
+        assert_eq!(
+            document["servers"][0]["url"],
+            json!("http://localhost:8080")
+        );
+
+        let paths: &Map<String, Value> = document["paths"].as_object().unwrap();
+        assert_eq!(
+            paths.keys().collect::<Vec<&String>>(),
+            vec![
+                "/time",
+                "/workout",
+                "/workout/{id}",
+                "/workout/{workout_id}/tag/{tag}"
+            ]
+        );
+
+        let create: &Value = &document["paths"]["/workout"]["post"];
+        assert_eq!(create["operationId"], json!("post_workout"));
+        assert_eq!(
+            create["requestBody"]["content"]["application/json"]["schema"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "completed": {"type": "boolean"},
+                    "duration_minutes": {"type": "number"},
+                    "id": {"type": "number"},
+                    "name": {"type": "string"}
+                },
+                "required": ["completed", "duration_minutes", "id", "name"]
+            })
+        );
+
+        // The PATCH route names its parameter differently and shares the path of GET and DELETE
+        let patch: &Value = &document["paths"]["/workout/{id}"]["patch"];
+        assert_eq!(
+            patch["parameters"],
+            json!([{"name": "id", "in": "path", "required": true, "schema": {"type": "string"}}])
+        );
+        let delete: &Value = &document["paths"]["/workout/{id}"]["delete"];
+        assert_eq!(
+            delete["responses"]["200"],
+            json!({"description": "Successful response"})
+        );
+        assert!(delete.get("requestBody").is_none());
+
+        let delete_tag: &Value = &document["paths"]["/workout/{workout_id}/tag/{tag}"]["delete"];
+        assert_eq!(
+            delete_tag["parameters"][0]["schema"],
+            json!({"type": "integer"})
+        );
+        assert_eq!(
+            delete_tag["parameters"][1]["schema"],
+            json!({"type": "string", "pattern": "^\\w+$"})
+        );
+        assert_eq!(
+            document["paths"]["/time"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"],
+            json!({})
+        );
+    }
+
+    #[test]
+    fn tests_validate_openapi_reports_broken_documents() {
+        let document: Value = json!({
+            "openapi": "3.0.3",
+            "info": {"title": "API"},
+            "paths": {
+                "/task/{id}": {
+                    "get": {
+                        "operationId": "task",
+                        "responses": {"200": {"description": "ok"}}
+                    },
+                    "fetch": {}
+                },
+                "/task/{task_id}": {
+                    "delete": {
+                        "operationId": "task",
+                        "parameters": [{"name": "task_id", "in": "path", "schema": {"type": 5}}],
+                        "responses": {"ok": {}}
+                    }
+                }
+            }
+        });
+
+        assert_eq!(
+            validate_openapi(&document),
+            Err(vec![
+                "/info: \"version\" is a required property".to_string(),
+                "/openapi: \"3.0.3\" does not match \"^3\\.1\\.\\d+(-.+)?$\"".to_string(),
+                "/paths/~1task~1{id}: Unevaluated properties are not allowed ('fetch' was unexpected)".to_string(),
+                "/paths/~1task~1{task_id}/delete/parameters/0: \"required\" is a required property".to_string(),
+                "/paths/~1task~1{task_id}/delete/responses: Unevaluated properties are not allowed ('ok' was unexpected)".to_string(),
+                "GET /task/{id}: path parameter `id` is not declared".to_string(),
+                "/task/{id} and /task/{task_id} only differ in their parameter names".to_string(),
+                "DELETE /task/{task_id}: operationId `task` is not unique".to_string(),
+                "DELETE /task/{task_id}: invalid schema {\"type\":5}: 5 is not valid under any of the schemas listed in the 'anyOf' keyword".to_string(),
+            ])
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// How much of the end of a process's output is kept, i.e. for the fix prompt
+pub const OUTPUT_TAIL_BYTES: usize = 64 * 1024;
+
+// Output pipe of a child process read on a background thread, so a chatty process never blocks
+// on a full pipe. Only the last OUTPUT_TAIL_BYTES are kept.
+#[derive(Debug)]
+pub struct OutputDrain {
+    tail: Arc<Mutex<OutputTail>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+// Last bytes a process wrote, older ones are dropped
+#[derive(Debug, Default)]
+struct OutputTail {
+    bytes: VecDeque<u8>,
+    truncated: bool,
+}
+
+impl OutputTail {
+    fn push(&mut self, chunk: &[u8]) {
+        self.bytes.extend(chunk);
+        let excess: usize = self.bytes.len().saturating_sub(OUTPUT_TAIL_BYTES);
+        if excess > 0 {
+            self.bytes.drain(..excess);
+            self.truncated = true;
+        }
+    }
+
+    fn text(&self) -> String {
+        let bytes: Vec<u8> = self.bytes.iter().copied().collect();
+        let text: String = String::from_utf8_lossy(&bytes).to_string();
+        if self.truncated {
+            format!("[earlier output dropped]\n{}", text)
+        } else {
+            text
+        }
+    }
+}
+
+impl OutputDrain {
+    // Reads the pipe until it closes, no pipe gives an empty output
+    pub fn spawn(pipe: Option<impl Read + Send + 'static>) -> Self {
+        let tail: Arc<Mutex<OutputTail>> = Arc::default();
+        let reader: Option<JoinHandle<()>> = pipe.map(|pipe| read_into(pipe, tail.clone()));
+        Self { tail, reader }
+    }
+
+    // What the process wrote so far
+    pub fn text(&self) -> String {
+        self.tail.lock().map(|tail| tail.text()).unwrap_or_default()
+    }
+
+    // Waits up to `timeout` for the pipe to close after the process ended, then returns the output
+    // A process the child detached may keep the pipe open, so the reader is not joined
+    pub fn finish(&mut self, timeout: Duration) -> String {
+        let deadline: Instant = Instant::now() + timeout;
+        if let Some(reader) = self.reader.take() {
+            while !reader.is_finished() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        self.text()
+    }
+}
+
+fn read_into(mut pipe: impl Read + Send + 'static, tail: Arc<Mutex<OutputTail>>) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut chunk: [u8; 8192] = [0; 8192];
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) | Err(_) => return,
+                Ok(read) => match tail.lock() {
+                    Ok(mut tail) => tail.push(&chunk[..read]),
+                    Err(_) => return,
+                },
+            }
+        }
+    })
+}
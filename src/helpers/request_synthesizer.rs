@@ -0,0 +1,429 @@
+use crate::models::agents::agent_traits::RouteObject;
+use crate::models::general::route::{path_parameter, FieldType, HttpMethod};
+
+use reqwest::{Client, Method};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+// Request built from the schema of one endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynthesizedRequest {
+    pub method: Method,
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+// i.e. POST /task with body {"name":"sample"}, used in failure reports for the fix loop
+impl fmt::Display for SynthesizedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+        if let Some(body) = &self.body {
+            write!(f, " with body {}", body)?;
+        }
+        Ok(())
+    }
+}
+
+// Builds requests for every endpoint of the API schema
+// Path parameters and ids are filled with the values of earlier requests and responses, so i.e.
+// the task created by POST /task is read with GET /task/{id} and removed with DELETE /task/{id}
+#[derive(Debug, Default)]
+pub struct RequestSynthesizer {
+    known_values: HashMap<String, Value>,
+}
+
+impl RequestSynthesizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Orders the endpoints into a create, read, update, delete sequence per resource
+    pub fn crud_order(endpoints: &[RouteObject]) -> Vec<RouteObject> {
+        let mut ordered: Vec<RouteObject> = endpoints.to_vec();
+        ordered.sort_by_key(|endpoint: &RouteObject| {
+            (
+                resource_of(&endpoint.route).to_string(),
+                method_rank(endpoint.method),
+                endpoint.is_route_dynamic,
+            )
+        });
+        ordered
+    }
+
+    pub fn request(&self, endpoint: &RouteObject) -> SynthesizedRequest {
+        let path: String = endpoint
+            .route
+            .split('/')
+            .map(|segment: &str| match path_parameter(segment) {
+                Some(name) => self.path_value(name),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join("/");
+
+        let has_body: bool = endpoint.method.has_request_body()
+            && matches!(
+                endpoint.request_body,
+                FieldType::Object(_) | FieldType::Array(_)
+            );
+        let body: Option<Value> = has_body.then(|| match &endpoint.request_body {
+            // Reuse known ids so updates target what was created
+            FieldType::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, field_type)| {
+                        let value: Value = self
+                            .known_values
+                            .get(key)
+                            .filter(|_| field_type.is_scalar())
+                            .cloned()
+                            .unwrap_or_else(|| sample_value(field_type));
+                        (key.clone(), value)
+                    })
+                    .collect::<Map<String, Value>>(),
+            ),
+            field_type => sample_value(field_type),
+        });
+
+        SynthesizedRequest {
+            method: endpoint.method.into(),
+            path,
+            body,
+        }
+    }
+
+    // Remembers the fields of a successful request and its response for later path parameters
+    pub fn record(&mut self, request: &SynthesizedRequest, response: &Value) {
+        for value in [request.body.as_ref(), Some(response)]
+            .into_iter()
+            .flatten()
+        {
+            if let Value::Object(fields) = value {
+                for (key, value) in fields {
+                    if is_scalar(value) && !value.is_null() {
+                        self.known_values.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // Sends the request for the endpoint and checks the status and the shape of the response
+    pub async fn check_endpoint(
+        &mut self,
+        client: &Client,
+        base_url: &str,
+        endpoint: &RouteObject,
+    ) -> Result<(), String> {
+        let request: SynthesizedRequest = self.request(endpoint);
+        let url: String = format!("{}{}", base_url.trim_end_matches('/'), request.path);
+
+        let mut builder: reqwest::RequestBuilder = client.request(request.method.clone(), &url);
+        if let Some(body) = &request.body {
+            builder = builder.json(body);
+        }
+        let response: reqwest::Response = builder
+            .send()
+            .await
+            .map_err(|e| format!("{} failed: {}", request, e))?;
+
+        let status: u16 = response.status().as_u16();
+        let text: String = response.text().await.unwrap_or_default();
+        if !(200..300).contains(&status) {
+            return Err(format!("{} returned status {}: {}", request, status, text));
+        }
+
+        let body: Value = if text.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&text).unwrap_or(Value::String(text))
+        };
+        validate_response(&endpoint.response, &body)
+            .map_err(|e| format!("{} returned an unexpected response: {}", request, e))?;
+
+        self.record(&request, &body);
+        Ok(())
+    }
+
+    fn path_value(&self, name: &str) -> String {
+        let value: Option<&Value> = self.known_values.get(name).or_else(|| {
+            // i.e. {task_id} is the id of the created task
+            name.ends_with("id")
+                .then(|| self.known_values.get("id"))
+                .flatten()
+        });
+
+        match value {
+            Some(Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+            None if name.ends_with("id") => "1".to_string(),
+            None => "sample".to_string(),
+        }
+    }
+}
+
+// Checks a response against the schema of the endpoint
+// None and Any accept every response, the model marks responses it cannot infer so
+pub fn validate_response(schema: &FieldType, value: &Value) -> Result<(), String> {
+    let matches: bool = match schema {
+        FieldType::None | FieldType::Any => true,
+        FieldType::String => value.is_string(),
+        FieldType::Number => value.is_number(),
+        FieldType::Integer => value.is_i64() || value.is_u64(),
+        FieldType::Bool => value.is_boolean(),
+        FieldType::Object(fields) => {
+            let Value::Object(values) = value else {
+                return Err(format!("expected an object, got {}", value));
+            };
+            for (key, field_type) in fields {
+                let field_value: &Value = values
+                    .get(key)
+                    .ok_or_else(|| format!("field `{}` is missing", key))?;
+                validate_response(field_type, field_value)
+                    .map_err(|e| format!("field `{}`: {}", key, e))?;
+            }
+            true
+        }
+        FieldType::Array(item_type) => {
+            let Value::Array(values) = value else {
+                return Err(format!("expected an array, got {}", value));
+            };
+            return values
+                .iter()
+                .try_for_each(|item: &Value| validate_response(item_type, item));
+        }
+    };
+
+    if matches {
+        Ok(())
+    } else {
+        Err(format!("expected {}, got {}", schema, value))
+    }
+}
+
+// Value of the given type for request bodies
+fn sample_value(field_type: &FieldType) -> Value {
+    match field_type {
+        FieldType::None => Value::Null,
+        FieldType::Any | FieldType::String => json!("sample"),
+        FieldType::Number | FieldType::Integer => json!(1),
+        FieldType::Bool => json!(true),
+        FieldType::Array(item_type) => Value::Array(vec![sample_value(item_type)]),
+        FieldType::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field_type)| (key.clone(), sample_value(field_type)))
+                .collect::<Map<String, Value>>(),
+        ),
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !(value.is_object() || value.is_array())
+}
+
+// /task/{id} and /task belong to the resource /task
+fn resource_of(route: &str) -> &str {
+    match route.find("/{") {
+        Some(index) => &route[..index],
+        None => route.trim_end_matches('/'),
+    }
+}
+
+fn method_rank(method: HttpMethod) -> u8 {
+    match method {
+        HttpMethod::Post => 0,
+        HttpMethod::Get => 1,
+        HttpMethod::Put => 2,
+        HttpMethod::Patch => 3,
+        HttpMethod::Delete => 4,
+        HttpMethod::Head | HttpMethod::Options => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn schema_endpoints() -> Vec<RouteObject> {
+        serde_json::from_str(include_str!(
+            "../../fixtures/llm/print_rest_api_endpoints.txt"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn tests_crud_order_and_path_parameters() {
+        let ordered: Vec<RouteObject> = RequestSynthesizer::crud_order(&schema_endpoints());
+        let sequence: Vec<String> = ordered
+            .iter()
+            .map(|endpoint: &RouteObject| format!("{} {}", endpoint.method, endpoint.route))
+            .collect();
+        assert_eq!(
+            sequence,
+            vec![
+                "GET /time",
+                "POST /workout",
+                "GET /workout",
+                "GET /workout/{id}",
+                "PUT /workout",
+                "DELETE /workout/{id}",
+            ]
+        );
+
+        let mut synthesizer: RequestSynthesizer = RequestSynthesizer::new();
+        let create: SynthesizedRequest = synthesizer.request(&ordered[1]);
+        assert_eq!(create.method, Method::POST);
+        assert_eq!(create.body.as_ref().unwrap()["completed"], json!(true));
+
+        synthesizer.record(&create, &json!({"id": 42, "name": "run"}));
+        let read: SynthesizedRequest = synthesizer.request(&ordered[3]);
+        assert_eq!(read.path, "/workout/42");
+        assert_eq!(read.body, None);
+        let update: SynthesizedRequest = synthesizer.request(&ordered[4]);
+        assert_eq!(update.body.unwrap()["id"], json!(42));
+    }
+
+    #[test]
+    fn tests_validate_response() {
+        let schema: FieldType =
+            FieldType::from(json!([{"id": "number", "name": "string", "completed": "bool"}]));
+
+        assert!(validate_response(
+            &schema,
+            &json!([{"id": 1, "name": "a", "completed": false}])
+        )
+        .is_ok());
+        assert_eq!(
+            validate_response(
+                &schema,
+                &json!([{"id": "1", "name": "a", "completed": false}])
+            ),
+            Err("field `id`: expected number, got \"1\"".to_string())
+        );
+        assert_eq!(
+            validate_response(&schema, &json!({"id": 1})),
+            Err("expected an array, got {\"id\":1}".to_string())
+        );
+        assert!(validate_response(&FieldType::from(json!("None")), &Value::Null).is_ok());
+    }
+
+    // Minimal CRUD server for /workout which answers with the stored items
+    async fn spawn_fake_crud_server() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: String = format!("http://{}", listener.local_addr().unwrap());
+        let items: Arc<Mutex<HashMap<u64, Value>>> = Arc::new(Mutex::new(HashMap::new()));
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let items: Arc<Mutex<HashMap<u64, Value>>> = items.clone();
+                tokio::spawn(async move {
+                    let mut buf: Vec<u8> = vec![];
+                    let mut chunk: [u8; 4096] = [0; 4096];
+                    loop {
+                        let n: usize = socket.read(&mut chunk).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        buf.extend_from_slice(&chunk[..n]);
+
+                        let text: String = String::from_utf8_lossy(&buf).to_string();
+                        let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                            continue;
+                        };
+                        let content_length: usize = head
+                            .lines()
+                            .find_map(|line| {
+                                let (name, value) = line.split_once(':')?;
+                                if name.eq_ignore_ascii_case("content-length") {
+                                    value.trim().parse().ok()
+                                } else {
+                                    None
+                                }
+                            })
+                            .unwrap_or(0);
+                        if body.len() < content_length {
+                            continue;
+                        }
+
+                        let mut request_line = head.lines().next().unwrap().split(' ');
+                        let method: &str = request_line.next().unwrap();
+                        let path: &str = request_line.next().unwrap();
+                        let request: Value =
+                            serde_json::from_str(&body[..content_length]).unwrap_or(Value::Null);
+                        let id: Option<u64> = path
+                            .strip_prefix("/workout/")
+                            .and_then(|id: &str| id.parse().ok());
+
+                        let (status, reply): (u16, Value) = {
+                            let mut items = items.lock().unwrap();
+                            match (method, id) {
+                                ("POST", None) | ("PUT", None) => {
+                                    let id: u64 = request["id"].as_u64().unwrap_or(0);
+                                    items.insert(id, request.clone());
+                                    (200, request)
+                                }
+                                ("GET", None) if path == "/time" => (200, json!("12:00")),
+                                ("GET", None) => {
+                                    (200, Value::Array(items.values().cloned().collect()))
+                                }
+                                ("GET", Some(id)) => match items.get(&id) {
+                                    Some(item) => (200, item.clone()),
+                                    None => (404, Value::Null),
+                                },
+                                ("DELETE", Some(id)) => match items.remove(&id) {
+                                    Some(_) => (200, Value::Null),
+                                    None => (404, Value::Null),
+                                },
+                                _ => (404, Value::Null),
+                            }
+                        };
+
+                        let reply_str: String = reply.to_string();
+                        let response: String = format!(
+                            "HTTP/1.1 {} OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                            status,
+                            reply_str.len(),
+                            reply_str
+                        );
+                        let _ = socket.write_all(response.as_bytes()).await;
+                        buf.clear();
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn tests_crud_sequence_against_server() {
+        let base_url: String = spawn_fake_crud_server().await;
+        let client: Client = Client::new();
+        let mut synthesizer: RequestSynthesizer = RequestSynthesizer::new();
+
+        for endpoint in RequestSynthesizer::crud_order(&schema_endpoints()) {
+            synthesizer
+                .check_endpoint(&client, &base_url, &endpoint)
+                .await
+                .unwrap_or_else(|e| panic!("{}", e));
+        }
+
+        // The item was deleted at the end of the sequence
+        let read: RouteObject = schema_endpoints()
+            .into_iter()
+            .find(|endpoint: &RouteObject| endpoint.route == "/workout/{id}")
+            .unwrap();
+        assert_eq!(
+            synthesizer.check_endpoint(&client, &base_url, &read).await,
+            Err("GET /workout/1 returned status 404: null".to_string())
+        );
+    }
+}
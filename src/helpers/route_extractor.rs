@@ -0,0 +1,740 @@
+use crate::models::agents::agent_traits::RouteObject;
+use crate::models::general::error::WizError;
+use crate::models::general::route::{route_key, FieldType, HttpMethod};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{
+    Expr, ExprMethodCall, Fields, FnArg, GenericArgument, Item, ItemFn, ItemStruct, Lit,
+    PathArguments, ReturnType, Token, Type,
+};
+
+// How a handler was attached to the app, a scope or a resource
+#[derive(Debug)]
+enum Registration {
+    // .route(path, web::get().to(handler)), the handler is None for closures
+    Route {
+        method: HttpMethod,
+        path: String,
+        handler: Option<String>,
+    },
+    // .service(handler) of a handler with a #[get("/path")] style attribute
+    Service {
+        prefix: String,
+        handler: String,
+    },
+}
+
+// Endpoints of actix-web code, read from the source instead of asking the model
+// Collects structs, functions and route registrations of every file added, so handlers and
+// their body types can live in other modules than the App
+#[derive(Default)]
+pub struct RouteExtractor {
+    structs: HashMap<String, ItemStruct>,
+    handlers: HashMap<String, ItemFn>,
+    registrations: Vec<Registration>,
+}
+
+impl RouteExtractor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Parses one file of the crate and returns the modules it declares with `mod name;`
+    pub fn add_source(&mut self, source: &str) -> Result<Vec<String>, syn::Error> {
+        let file: syn::File = syn::parse_file(source)?;
+        self.visit_file(&file);
+        Ok(file
+            .items
+            .iter()
+            .filter_map(|item: &Item| match item {
+                Item::Mod(module) if module.content.is_none() => Some(module.ident.to_string()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    // Registered endpoints in the order of the source, with the types of their handlers
+    pub fn routes(&self) -> Vec<RouteObject> {
+        let mut routes: Vec<RouteObject> = vec![];
+        for registration in &self.registrations {
+            let endpoints: Vec<(HttpMethod, String, Option<&str>)> = match registration {
+                Registration::Route {
+                    method,
+                    path,
+                    handler,
+                } => vec![(*method, path.clone(), handler.as_deref())],
+                Registration::Service { prefix, handler } => self
+                    .handlers
+                    .get(handler)
+                    .map(attribute_routes)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(method, path)| {
+                        (method, join_path(prefix, &path), Some(handler.as_str()))
+                    })
+                    .collect(),
+            };
+
+            for (method, path, handler) in endpoints {
+                let is_registered: bool = routes
+                    .iter()
+                    .any(|route: &RouteObject| route.method == method && route.route == path);
+                if !is_registered {
+                    routes.push(self.route_object(method, path, handler));
+                }
+            }
+        }
+        routes
+    }
+
+    fn route_object(
+        &self,
+        method: HttpMethod,
+        route: String,
+        handler: Option<&str>,
+    ) -> RouteObject {
+        let mut endpoint: RouteObject = RouteObject {
+            is_route_dynamic: false,
+            method,
+            request_body: FieldType::None,
+            response: FieldType::Any,
+            route,
+            path_types: BTreeMap::new(),
+        };
+        endpoint.is_route_dynamic = !endpoint.path_parameters().is_empty();
+
+        let Some(func) = handler.and_then(|handler: &str| self.handlers.get(handler)) else {
+            return endpoint;
+        };
+        for input in &func.sig.inputs {
+            let FnArg::Typed(argument) = input else {
+                continue;
+            };
+            match type_wrapper(&argument.ty) {
+                Some((name, inner)) if name == "Json" || name == "Form" => {
+                    endpoint.request_body = self.field_type(inner, &mut vec![]);
+                }
+                Some((name, inner)) if name == "Path" => {
+                    endpoint.path_types = self.path_types(&endpoint.path_parameters(), inner);
+                }
+                _ => {}
+            }
+        }
+        endpoint.response = self.response_type(&func.sig.output);
+        endpoint
+    }
+
+    // web::Path<u64>, web::Path<(u64, String)> and web::Path<StructWithParams> by parameter name
+    fn path_types(&self, parameters: &[&str], ty: &Type) -> BTreeMap<String, FieldType> {
+        if let Type::Tuple(tuple) = ty {
+            return parameters
+                .iter()
+                .zip(tuple.elems.iter())
+                .map(|(name, ty)| (name.to_string(), self.field_type(ty, &mut vec![])))
+                .collect();
+        }
+
+        match self.field_type(ty, &mut vec![]) {
+            FieldType::Object(fields) => parameters
+                .iter()
+                .filter_map(|name: &&str| {
+                    fields
+                        .get(*name)
+                        .map(|field: &FieldType| (name.to_string(), field.clone()))
+                })
+                .collect(),
+            field if parameters.len() == 1 => BTreeMap::from([(parameters[0].to_string(), field)]),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    // Only Json<T> and plain strings have a known shape, HttpResponse and impl Responder do not
+    fn response_type(&self, output: &ReturnType) -> FieldType {
+        let ReturnType::Type(_, ty) = output else {
+            return FieldType::None;
+        };
+        let ty: &Type = match type_wrapper(ty) {
+            Some((name, inner)) if name == "Result" => inner,
+            _ => ty,
+        };
+        match type_wrapper(ty) {
+            Some((name, inner)) if name == "Json" => self.field_type(inner, &mut vec![]),
+            _ => match self.field_type(ty, &mut vec![]) {
+                FieldType::String => FieldType::String,
+                _ => FieldType::Any,
+            },
+        }
+    }
+
+    // `visiting` holds the structs being expanded, a struct containing itself becomes Any
+    fn field_type(&self, ty: &Type, visiting: &mut Vec<String>) -> FieldType {
+        match ty {
+            Type::Reference(reference) => self.field_type(&reference.elem, visiting),
+            Type::Paren(paren) => self.field_type(&paren.elem, visiting),
+            Type::Group(group) => self.field_type(&group.elem, visiting),
+            Type::Slice(slice) => {
+                FieldType::Array(Box::new(self.field_type(&slice.elem, visiting)))
+            }
+            Type::Array(array) => {
+                FieldType::Array(Box::new(self.field_type(&array.elem, visiting)))
+            }
+            Type::Tuple(tuple) if tuple.elems.is_empty() => FieldType::None,
+            Type::Path(_) => {
+                let Some((name, argument)) = type_name(ty) else {
+                    return FieldType::Any;
+                };
+                let inner = |visiting: &mut Vec<String>| {
+                    argument.map_or(FieldType::Any, |argument: &Type| {
+                        self.field_type(argument, visiting)
+                    })
+                };
+                match name.as_str() {
+                    "Option" | "Box" | "Rc" | "Arc" | "Json" => inner(visiting),
+                    "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => {
+                        FieldType::Array(Box::new(inner(visiting)))
+                    }
+                    _ => match FieldType::from_type_name(&name) {
+                        FieldType::Any => self.struct_type(&name, visiting),
+                        field => field,
+                    },
+                }
+            }
+            _ => FieldType::Any,
+        }
+    }
+
+    fn struct_type(&self, name: &str, visiting: &mut Vec<String>) -> FieldType {
+        let Some(item) = self.structs.get(name) else {
+            return FieldType::Any;
+        };
+        if visiting.iter().any(|visited: &String| visited == name) {
+            return FieldType::Any;
+        }
+
+        visiting.push(name.to_string());
+        let field_type: FieldType = match &item.fields {
+            Fields::Named(fields) => FieldType::Object(
+                fields
+                    .named
+                    .iter()
+                    .filter_map(|field| {
+                        let name: String = field.ident.as_ref()?.to_string();
+                        Some((name, self.field_type(&field.ty, visiting)))
+                    })
+                    .collect(),
+            ),
+            // Newtypes serialize as their inner value
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                self.field_type(&fields.unnamed[0].ty, visiting)
+            }
+            _ => FieldType::Any,
+        };
+        visiting.pop();
+        field_type
+    }
+
+    // Registrations of a method call chain, i.e. App::new().route(..).service(..)
+    // Receivers come first so the registrations keep the order of the source
+    fn collect_chain(&mut self, call: &ExprMethodCall, base: &str) {
+        if let Expr::MethodCall(receiver) = call.receiver.as_ref() {
+            self.collect_chain(receiver, base);
+        }
+
+        let args: Vec<&Expr> = call.args.iter().collect();
+        match (call.method.to_string().as_str(), args.as_slice()) {
+            ("route", [path, route]) => {
+                if let Some(path) = string_literal(path) {
+                    self.collect_route(&join_path(base, &path), route);
+                }
+            }
+            // Routes of web::resource(path) have no path of their own
+            ("route", [route]) => self.collect_route(base, route),
+            ("service", [service]) => self.collect_service(service, base),
+            _ => {}
+        }
+    }
+
+    fn collect_route(&mut self, path: &str, route: &Expr) {
+        let mut method: Option<HttpMethod> = None;
+        let mut handler: Option<String> = None;
+        let mut current: &Expr = route;
+        loop {
+            match current {
+                Expr::MethodCall(call) => {
+                    match call.method.to_string().as_str() {
+                        "to" => handler = call.args.first().and_then(last_segment),
+                        // Route::new().method(Method::GET)
+                        "method" => method = call.args.first().and_then(method_of),
+                        _ => {}
+                    }
+                    current = &call.receiver;
+                }
+                // web::get() or web::method(Method::GET)
+                Expr::Call(call) => {
+                    method = method.or_else(|| match last_segment(&call.func).as_deref() {
+                        Some("method") => call.args.first().and_then(method_of),
+                        Some(name) => HttpMethod::try_from(name.to_string()).ok(),
+                        None => None,
+                    });
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        if let Some(method) = method {
+            self.registrations.push(Registration::Route {
+                method,
+                path: path.to_string(),
+                handler,
+            });
+        }
+    }
+
+    fn collect_service(&mut self, service: &Expr, base: &str) {
+        match service {
+            Expr::Path(_) => {
+                if let Some(handler) = last_segment(service) {
+                    self.registrations.push(Registration::Service {
+                        prefix: base.to_string(),
+                        handler,
+                    });
+                }
+            }
+            Expr::Tuple(tuple) => {
+                for service in &tuple.elems {
+                    self.collect_service(service, base);
+                }
+            }
+            // web::scope("/api").route(..) and web::resource("/task").route(..)
+            Expr::MethodCall(call) => {
+                if let Some(path) = chain_root_path(service) {
+                    self.collect_chain(call, &join_path(base, &path));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for RouteExtractor {
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        self.structs.insert(item.ident.to_string(), item.clone());
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.handlers
+            .insert(item.sig.ident.to_string(), item.clone());
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        // The outermost call of a registration chain collects the whole chain
+        match call.method.to_string().as_str() {
+            "route" | "service" => self.collect_chain(call, ""),
+            _ => visit::visit_expr_method_call(self, call),
+        }
+    }
+}
+
+// Endpoints of the web server crate: main.rs and the modules it declares with `mod name;`
+// The inner Err tells why the code could not be parsed, the caller falls back to the model then
+pub fn extract_routes(main_path: &Path) -> Result<Result<Vec<RouteObject>, String>, WizError> {
+    let mut extractor: RouteExtractor = RouteExtractor::new();
+    let mut pending: Vec<PathBuf> = vec![main_path.to_path_buf()];
+
+    while let Some(path) = pending.pop() {
+        let source: String =
+            fs::read_to_string(&path).map_err(|e| WizError::file_io(path.display(), e))?;
+        let modules: Vec<String> = match extractor.add_source(&source) {
+            Ok(modules) => modules,
+            Err(e) => return Ok(Err(format!("{}: {}", path.display(), e))),
+        };
+        pending.extend(
+            modules
+                .iter()
+                .filter_map(|module_name: &String| module_path(&path, module_name)),
+        );
+    }
+    Ok(Ok(extractor.routes()))
+}
+
+// Where the model's endpoints differ from the ones read from the code
+pub fn route_disagreements(extracted: &[RouteObject], reported: &[RouteObject]) -> Vec<String> {
+    let mut disagreements: Vec<String> = vec![];
+
+    for endpoint in extracted {
+        let Some(reported_endpoint) = find_route(reported, endpoint) else {
+            disagreements.push(format!(
+                "{} {} is served by the code but missing from the endpoints of the model",
+                endpoint.method, endpoint.route
+            ));
+            continue;
+        };
+        if !endpoint
+            .request_body
+            .agrees_with(&reported_endpoint.request_body)
+        {
+            disagreements.push(format!(
+                "{} {} takes {} but the model printed {}",
+                endpoint.method,
+                endpoint.route,
+                endpoint.request_body,
+                reported_endpoint.request_body
+            ));
+        }
+        if !endpoint.response.agrees_with(&reported_endpoint.response) {
+            disagreements.push(format!(
+                "{} {} returns {} but the model printed {}",
+                endpoint.method, endpoint.route, endpoint.response, reported_endpoint.response
+            ));
+        }
+    }
+
+    for reported_endpoint in reported {
+        if find_route(extracted, reported_endpoint).is_none() {
+            disagreements.push(format!(
+                "{} {} is printed by the model but not served by the code",
+                reported_endpoint.method, reported_endpoint.route
+            ));
+        }
+    }
+    disagreements
+}
+
+// Takes the types the code does not tell, i.e. what an `impl Responder` handler returns, from the model
+pub fn fill_unknown_types(extracted: &mut [RouteObject], reported: &[RouteObject]) {
+    for endpoint in extracted.iter_mut() {
+        let Some(reported_endpoint) = find_route(reported, endpoint) else {
+            continue;
+        };
+        if endpoint.request_body == FieldType::Any {
+            endpoint.request_body = reported_endpoint.request_body.clone();
+        }
+        if endpoint.response == FieldType::Any {
+            endpoint.response = reported_endpoint.response.clone();
+        }
+    }
+}
+
+// Same method and route, whatever the path parameters are called
+fn find_route<'a>(endpoints: &'a [RouteObject], endpoint: &RouteObject) -> Option<&'a RouteObject> {
+    let key: String = route_key(&endpoint.route);
+    endpoints.iter().find(|candidate: &&RouteObject| {
+        candidate.method == endpoint.method && route_key(&candidate.route) == key
+    })
+}
+
+// src/name.rs or src/name/mod.rs of `mod name;` in the file at `path`
+fn module_path(path: &Path, module_name: &str) -> Option<PathBuf> {
+    let file_stem: &str = path.file_stem()?.to_str()?;
+    let dir: PathBuf = match file_stem {
+        "main" | "lib" | "mod" => path.parent()?.to_path_buf(),
+        _ => path.parent()?.join(file_stem),
+    };
+    [
+        dir.join(format!("{}.rs", module_name)),
+        dir.join(module_name).join("mod.rs"),
+    ]
+    .into_iter()
+    .find(|candidate: &PathBuf| candidate.exists())
+}
+
+// #[get("/task/{id}")] and #[route("/task", method = "GET", method = "HEAD")]
+fn attribute_routes(func: &ItemFn) -> Vec<(HttpMethod, String)> {
+    let mut routes: Vec<(HttpMethod, String)> = vec![];
+    for attr in &func.attrs {
+        let Some(name) = attr
+            .path()
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+        else {
+            continue;
+        };
+        let Ok(args) = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+            continue;
+        };
+        let Some(path) = args.first().and_then(string_literal) else {
+            continue;
+        };
+
+        if name == "route" {
+            for arg in args.iter().skip(1) {
+                if let Expr::Assign(assign) = arg {
+                    let is_method: bool = last_segment(&assign.left).as_deref() == Some("method");
+                    let method: Option<HttpMethod> = string_literal(&assign.right)
+                        .and_then(|method: String| HttpMethod::try_from(method).ok());
+                    if let (true, Some(method)) = (is_method, method) {
+                        routes.push((method, path.clone()));
+                    }
+                }
+            }
+        } else if let Ok(method) = HttpMethod::try_from(name) {
+            routes.push((method, path));
+        }
+    }
+    routes
+}
+
+// Path of the web::scope(..) or web::resource(..) call a chain starts with
+fn chain_root_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::MethodCall(call) => chain_root_path(&call.receiver),
+        Expr::Call(call) => match last_segment(&call.func).as_deref() {
+            Some("scope" | "resource") => call.args.first().and_then(string_literal),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Name and first generic argument of a type, i.e. ("Json", Some(Task)) for web::Json<Task>
+fn type_name(ty: &Type) -> Option<(String, Option<&Type>)> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    let argument: Option<&Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    };
+    Some((segment.ident.to_string(), argument))
+}
+
+// Wrapper name and wrapped type, i.e. ("Path", (u64, String)) for web::Path<(u64, String)>
+fn type_wrapper(ty: &Type) -> Option<(String, &Type)> {
+    let (name, argument) = type_name(ty)?;
+    Some((name, argument?))
+}
+
+// Last segment of a path expression, i.e. `login` for auth::login
+fn last_segment(expr: &Expr) -> Option<String> {
+    let Expr::Path(path) = expr else {
+        return None;
+    };
+    path.path
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+}
+
+// Method::GET, http::Method::POST or "GET"
+fn method_of(expr: &Expr) -> Option<HttpMethod> {
+    let method: String = last_segment(expr).or_else(|| string_literal(expr))?;
+    HttpMethod::try_from(method).ok()
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(literal) => match &literal.lit {
+            Lit::Str(string) => Some(string.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// join_path("/api/", "/task") is /api/task, an empty path is the prefix itself
+fn join_path(prefix: &str, path: &str) -> String {
+    let prefix: &str = prefix.trim_end_matches('/');
+    let path: &str = path.trim_start_matches('/');
+    if path.is_empty() && !prefix.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{}/{}", prefix, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn summary(routes: &[RouteObject]) -> Vec<String> {
+        routes
+            .iter()
+            .map(|route: &RouteObject| format!("{} {}", route.method, route.route))
+            .collect()
+    }
+
+    #[test]
+    fn tests_routes_of_scopes_resources_and_attributes() {
+        let source: &str = r#"
+            use actix_web::{get, web, App, HttpResponse, HttpServer, Responder};
+
+            #[derive(Serialize, Deserialize)]
+            struct Task {
+                id: u64,
+                name: String,
+                tags: Vec<String>,
+                owner: Option<User>,
+            }
+
+            #[derive(Serialize, Deserialize)]
+            struct User {
+                name: String,
+                tasks: Vec<Task>,
+            }
+
+            async fn create_task(data: web::Data<AppState>, task: web::Json<Task>) -> impl Responder {
+                HttpResponse::Ok().finish()
+            }
+
+            async fn read_task(id: web::Path<u64>) -> Result<web::Json<Task>, actix_web::Error> {
+                todo!()
+            }
+
+            async fn read_tag(path: web::Path<(u64, String)>) -> String {
+                todo!()
+            }
+
+            #[get("/health")]
+            async fn health() -> impl Responder {
+                HttpResponse::Ok()
+            }
+
+            #[actix_web::main]
+            async fn main() -> std::io::Result<()> {
+                HttpServer::new(move || {
+                    App::new()
+                        .service(health)
+                        .route("/task", web::post().to(create_task))
+                        .service(
+                            web::scope("/api/")
+                                .service(web::resource("/task/{id}").route(web::get().to(read_task)))
+                                .route("/task/{id}/tag/{name:\\w+}", web::get().to(read_tag))
+                                .route("/ping", web::head().to(|| async { "pong" })),
+                        )
+                        .route("/task", web::post().to(create_task))
+                })
+                .bind("127.0.0.1:8080")?
+                .run()
+                .await
+            }
+        "#;
+
+        let mut extractor: RouteExtractor = RouteExtractor::new();
+        assert_eq!(extractor.add_source(source).unwrap(), Vec::<String>::new());
+        let routes: Vec<RouteObject> = extractor.routes();
+
+        assert_eq!(
+            summary(&routes),
+            vec![
+                "GET /health",
+                "POST /task",
+                "GET /api/task/{id}",
+                "GET /api/task/{id}/tag/{name:\\w+}",
+                "HEAD /api/ping",
+            ]
+        );
+
+        // The struct nesting back into Task is cut off
+        let task: FieldType = FieldType::from(json!({
+            "id": "integer",
+            "name": "string",
+            "tags": ["string"],
+            "owner": {"name": "string", "tasks": ["not_provided"]}
+        }));
+        assert_eq!(routes[1].request_body, task);
+        assert_eq!(routes[1].response, FieldType::Any);
+        assert!(!routes[1].is_route_dynamic);
+
+        assert!(routes[2].is_route_dynamic);
+        assert_eq!(routes[2].request_body, FieldType::None);
+        assert_eq!(routes[2].response, task);
+        assert_eq!(
+            routes[2].path_types,
+            BTreeMap::from([("id".to_string(), FieldType::Integer)])
+        );
+
+        assert_eq!(
+            routes[3].path_types,
+            BTreeMap::from([
+                ("id".to_string(), FieldType::Integer),
+                ("name".to_string(), FieldType::String)
+            ])
+        );
+        assert_eq!(routes[3].response, FieldType::String);
+    }
+
+    #[test]
+    fn tests_extract_routes_follows_modules_and_flags_the_model() {
+        let root: PathBuf =
+            std::env::temp_dir().join(format!("wizai_route_extractor_{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("auth.rs"),
+            include_str!("../../server-template/templates/auth.rs"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("main.rs"),
+            r#"
+                mod auth;
+                mod missing;
+
+                async fn delete_task(id: web::Path<u64>) -> impl Responder {
+                    HttpResponse::Ok().finish()
+                }
+
+                fn main() {
+                    App::new()
+                        .configure(auth::configure)
+                        .route("/task/{id}", web::delete().to(delete_task));
+                }
+            "#,
+        )
+        .unwrap();
+
+        let mut routes: Vec<RouteObject> = extract_routes(&root.join("main.rs")).unwrap().unwrap();
+        assert_eq!(
+            summary(&routes),
+            vec![
+                "DELETE /task/{id}",
+                "POST /register",
+                "POST /login",
+                "POST /logout"
+            ]
+        );
+        assert_eq!(
+            routes[1].request_body,
+            FieldType::from(json!({"username": "string", "password": "string"}))
+        );
+
+        let reported: Vec<RouteObject> = serde_json::from_value(json!([
+            {"route": "/register", "method": "post", "request_body": {"username": "string"}, "response": "string"},
+            {"route": "/login", "method": "post", "request_body": {"username": "string", "password": "string"}, "response": "string"},
+            {"route": "/task/{task_id}", "method": "delete", "request_body": "None", "response": "None"},
+            {"route": "/task", "method": "get", "request_body": "None", "response": ["string"]}
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            route_disagreements(&routes, &reported),
+            vec![
+                "POST /register takes {\"password\":\"string\",\"username\":\"string\"} but the model printed {\"username\":\"string\"}",
+                "POST /logout is served by the code but missing from the endpoints of the model",
+                "GET /task is printed by the model but not served by the code",
+            ]
+        );
+
+        fill_unknown_types(&mut routes, &reported);
+        assert_eq!(routes[0].response, FieldType::None);
+        assert_eq!(routes[2].response, FieldType::String);
+        assert_eq!(routes[3].response, FieldType::Any);
+
+        fs::write(root.join("main.rs"), "fn main() {").unwrap();
+        assert!(extract_routes(&root.join("main.rs")).unwrap().is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
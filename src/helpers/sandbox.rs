@@ -0,0 +1,475 @@
+use crate::helpers::command_line::PrintCommand;
+use crate::helpers::config::{SandboxConfig, WizConfig};
+use crate::helpers::general::copy_project_excluding;
+use crate::models::general::error::WizError;
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Parts of environment variable names which are never passed into the sandbox
+const SECRET_MARKERS: [&str; 6] = ["KEY", "TOKEN", "SECRET", "PASSWORD", "CREDENTIAL", "AUTH"];
+
+// System directories mounted read-only by bubblewrap
+const SYSTEM_DIRS: [&str; 5] = ["/usr", "/bin", "/lib", "/lib64", "/etc"];
+
+// Numbers the sandboxes of this process so their copies do not collide
+static SANDBOX_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// Resource argument of setrlimit, which glibc declares with its own type
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+type RlimitResource = libc::c_int;
+
+// How the processes of a sandbox are isolated from the machine
+#[derive(Debug, Clone, PartialEq)]
+pub enum Isolation {
+    // bubblewrap with only the toolchain, the copy and the build output mounted
+    Bubblewrap(PathBuf),
+    // Linux namespaces when bubblewrap is not installed: a user and network namespace for the
+    // build, only a user namespace for the server so it stays reachable on loopback
+    Namespaces,
+    // Only the copy, the environment and the resource limits
+    LimitsOnly,
+    // [sandbox] enabled = false: run in the project itself as before
+    Disabled,
+}
+
+// What runs in the sandbox: the build never gets network unless allowed, the server shares the
+// network of the machine so the endpoint tests can reach it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SandboxStep {
+    Build,
+    Run,
+}
+
+// Disposable copy of the web server project in which generated code is built and run
+// The copy is removed when the sandbox is dropped
+#[derive(Debug)]
+pub struct Sandbox {
+    settings: SandboxConfig,
+    root: Option<PathBuf>,
+    project_dir: PathBuf,
+    target_dir: PathBuf,
+    isolation: Isolation,
+    env_vars: Vec<(String, String)>,
+}
+
+impl Sandbox {
+    pub fn create(config: &WizConfig) -> Result<Self, WizError> {
+        let settings: SandboxConfig = config.sandbox.clone();
+        if !settings.enabled {
+            let project_dir: PathBuf = config.web_server_project_path();
+            let target_dir: PathBuf = match env::var_os("CARGO_TARGET_DIR") {
+                Some(target_dir) => project_dir.join(target_dir),
+                None => project_dir.join("target"),
+            };
+            return Ok(Self {
+                settings,
+                root: None,
+                project_dir,
+                target_dir,
+                isolation: Isolation::Disabled,
+                env_vars: env::vars().collect(),
+            });
+        }
+
+        let root: PathBuf = env::temp_dir().join(format!(
+            "wizai_sandbox_{}_{}",
+            std::process::id(),
+            SANDBOX_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let project_dir: PathBuf = root.join("project");
+        let target_dir: PathBuf = config.sandbox_target_path();
+        copy_project_excluding(
+            &config.web_server_project_path(),
+            &project_dir,
+            &agent_outputs(config),
+        )?;
+        fs::create_dir_all(&target_dir).map_err(|e| WizError::file_io(target_dir.display(), e))?;
+
+        let mut env_vars: Vec<(String, String)> = sandbox_env(env::vars(), &settings.env_allowlist);
+        env_vars.push((
+            "CARGO_TARGET_DIR".to_string(),
+            target_dir.display().to_string(),
+        ));
+
+        Ok(Self {
+            isolation: detect_isolation(&settings),
+            settings,
+            root: Some(root),
+            project_dir,
+            target_dir,
+            env_vars,
+        })
+    }
+
+    pub fn project_path(&self) -> &Path {
+        &self.project_dir
+    }
+
+    pub fn target_path(&self) -> &Path {
+        &self.target_dir
+    }
+
+    pub fn isolation(&self) -> &Isolation {
+        &self.isolation
+    }
+
+    // Command running the program inside the sandbox, in the project copy
+    pub fn command(&self, program: impl AsRef<OsStr>, step: SandboxStep) -> Command {
+        if self.isolation == Isolation::Disabled {
+            let mut command: Command = Command::new(program);
+            command.current_dir(&self.project_dir);
+            return command;
+        }
+
+        let has_network: bool = self.settings.allow_network || step == SandboxStep::Run;
+        let mut command: Command = match &self.isolation {
+            Isolation::Bubblewrap(bwrap) => {
+                let mut command: Command = Command::new(bwrap);
+                command
+                    .args(self.bubblewrap_args(has_network))
+                    .arg("--")
+                    .arg(program);
+                command
+            }
+            _ => Command::new(program),
+        };
+
+        command
+            .current_dir(&self.project_dir)
+            .env_clear()
+            .envs(self.env_vars.iter().cloned());
+        if !has_network {
+            command.env("CARGO_NET_OFFLINE", "true");
+        }
+
+        if step == SandboxStep::Run {
+            self.warn_unisolated_run();
+        }
+
+        let limits: [(RlimitResource, u64); 3] = [
+            (libc::RLIMIT_CPU, self.settings.cpu_seconds),
+            (libc::RLIMIT_AS, self.settings.memory_mb * 1024 * 1024),
+            (libc::RLIMIT_FSIZE, self.settings.file_size_mb * 1024 * 1024),
+        ];
+        let unshare_network: Option<bool> = match self.isolation {
+            Isolation::Namespaces => Some(!has_network),
+            _ => None,
+        };
+        // SAFETY: only async-signal-safe system calls run between fork and exec
+        unsafe {
+            command.pre_exec(move || {
+                for (resource, limit) in limits {
+                    let rlimit: libc::rlimit = libc::rlimit {
+                        rlim_cur: limit as libc::rlim_t,
+                        rlim_max: limit as libc::rlim_t,
+                    };
+                    if libc::setrlimit(resource, &rlimit) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                match unshare_network {
+                    Some(unshare_network) => unshare_namespaces(unshare_network),
+                    None => Ok(()),
+                }
+            });
+        }
+        command
+    }
+
+    // Only bubblewrap keeps the running server away from the files of the machine
+    fn warn_unisolated_run(&self) {
+        let warning: &str = match self.isolation {
+            Isolation::Namespaces => {
+                "Only bubblewrap isolates the running server, it runs in a user namespace with the files and network of the machine"
+            }
+            Isolation::LimitsOnly => {
+                "Only bubblewrap isolates the running server, it runs with resource limits only"
+            }
+            _ => return,
+        };
+        PrintCommand::Issue.print_agent_message("Sandbox", warning);
+    }
+
+    fn bubblewrap_args(&self, has_network: bool) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "--unshare-all",
+            "--die-with-parent",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--tmpfs",
+            "/tmp",
+        ]
+        .iter()
+        .map(|arg: &&str| arg.to_string())
+        .collect();
+        if has_network {
+            args.push("--share-net".to_string());
+        }
+
+        for dir in SYSTEM_DIRS {
+            args.extend([
+                "--ro-bind-try".to_string(),
+                dir.to_string(),
+                dir.to_string(),
+            ]);
+        }
+
+        // Toolchain read-only, the cargo home writable for the registry lock
+        let toolchain_dirs: [(&str, &str); 2] =
+            [("RUSTUP_HOME", "--ro-bind"), ("CARGO_HOME", "--bind")];
+        for (name, bind) in toolchain_dirs {
+            if let Some((_, dir)) = self.env_vars.iter().find(|(key, _)| key == name) {
+                args.extend([bind.to_string(), dir.clone(), dir.clone()]);
+            }
+        }
+
+        // After the tmpfs, the copy lives in the temporary directory
+        for dir in [&self.project_dir, &self.target_dir] {
+            let dir: String = dir.display().to_string();
+            args.extend(["--bind".to_string(), dir.clone(), dir]);
+        }
+        args
+    }
+
+    // Without network in the build the dependencies are downloaded first
+    // Fetching runs no code of the project
+    pub fn fetch_dependencies(&self) {
+        if self.settings.allow_network || self.isolation == Isolation::Disabled {
+            return;
+        }
+
+        let fetched: bool = Command::new("cargo")
+            .arg("fetch")
+            .current_dir(&self.project_dir)
+            .env_clear()
+            .envs(self.env_vars.iter().cloned())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+        if !fetched {
+            PrintCommand::Issue.print_agent_message(
+                "Sandbox",
+                "cargo fetch failed, building with the dependencies already downloaded",
+            );
+        }
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if let Some(root) = &self.root {
+            let _ = fs::remove_dir_all(root);
+        }
+    }
+}
+
+// Files other agents may be writing while a sandbox is created, which the server crate does not
+// use: the Smart Contract Developer's sources and solc output and the Frontend Developer's page
+fn agent_outputs(config: &WizConfig) -> Vec<PathBuf> {
+    vec![config.contracts_path(), config.frontend_output_path()]
+}
+
+// The allowed variables of the environment, without anything that looks like a secret
+pub fn sandbox_env(
+    vars: impl Iterator<Item = (String, String)>,
+    allowlist: &[String],
+) -> Vec<(String, String)> {
+    let mut env_vars: Vec<(String, String)> = vars
+        .filter(|(name, _)| allowlist.contains(name))
+        .filter(|(name, _)| {
+            let name: String = name.to_uppercase();
+            !SECRET_MARKERS
+                .iter()
+                .any(|marker: &&str| name.contains(marker))
+        })
+        .collect();
+
+    // The toolchain has to be found without HOME pointing at the real home directory
+    let home: Option<PathBuf> = env::var_os("HOME").map(PathBuf::from);
+    for (name, default_dir) in [("CARGO_HOME", ".cargo"), ("RUSTUP_HOME", ".rustup")] {
+        let is_set: bool = env_vars.iter().any(|(key, _)| key == name);
+        if let (false, Some(home)) = (is_set, &home) {
+            let dir: PathBuf = home.join(default_dir);
+            if dir.exists() {
+                env_vars.push((name.to_string(), dir.display().to_string()));
+            }
+        }
+    }
+    env_vars
+}
+
+// bubblewrap when it runs, otherwise namespaces when the kernel allows them
+fn detect_isolation(settings: &SandboxConfig) -> Isolation {
+    let has_bwrap: bool = Command::new(&settings.bwrap)
+        .args(["--ro-bind", "/", "/", "true"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+    if has_bwrap {
+        return Isolation::Bubblewrap(settings.bwrap.clone());
+    }
+
+    let mut probe: Command = Command::new("true");
+    // SAFETY: unshare is a single system call between fork and exec
+    unsafe {
+        probe.pre_exec(|| unshare_namespaces(true));
+    }
+    let has_namespaces: bool = probe
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+    if has_namespaces {
+        return Isolation::Namespaces;
+    }
+
+    PrintCommand::Issue.print_agent_message(
+        "Sandbox",
+        "Neither bubblewrap nor namespaces are available, only resource limits apply",
+    );
+    Isolation::LimitsOnly
+}
+
+// Moves the calling process into a new user namespace, and a new network namespace with only
+// loopback when `unshare_network` is set
+#[cfg(target_os = "linux")]
+fn unshare_namespaces(unshare_network: bool) -> io::Result<()> {
+    let mut flags: libc::c_int = libc::CLONE_NEWUSER;
+    if unshare_network {
+        flags |= libc::CLONE_NEWNET;
+    }
+    // SAFETY: unshare only changes the namespaces of the calling process
+    if unsafe { libc::unshare(flags) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// Namespaces are Linux only, elsewhere the sandbox falls back to the resource limits
+#[cfg(not(target_os = "linux"))]
+fn unshare_namespaces(_unshare_network: bool) -> io::Result<()> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sandboxes of the server template with their build output in a temporary directory
+    fn test_config(name: &str) -> (WizConfig, PathBuf) {
+        let target_dir: PathBuf = env::temp_dir().join(format!(
+            "wizai_sandbox_target_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let mut config: WizConfig = WizConfig::resolve(None).expect("Failed to load wizai.toml");
+        config.sandbox.target_dir = target_dir.clone();
+        (config, target_dir)
+    }
+
+    #[test]
+    fn tests_sandbox_env_strips_secrets() {
+        let vars: Vec<(String, String)> = vec![
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("OPEN_AI_KEY".to_string(), "sk-123".to_string()),
+            ("GITHUB_TOKEN".to_string(), "ghp".to_string()),
+            ("EDITOR".to_string(), "vim".to_string()),
+        ];
+        let allowlist: Vec<String> = vec![
+            "PATH".to_string(),
+            "OPEN_AI_KEY".to_string(),
+            "CARGO_HOME".to_string(),
+        ];
+
+        let env_vars: Vec<(String, String)> = sandbox_env(vars.into_iter(), &allowlist);
+
+        assert!(env_vars.contains(&("PATH".to_string(), "/usr/bin".to_string())));
+        assert!(!env_vars
+            .iter()
+            .any(|(name, _)| name == "OPEN_AI_KEY" || name == "GITHUB_TOKEN" || name == "EDITOR"));
+    }
+
+    #[test]
+    fn tests_sandbox_runs_in_a_disposable_copy() {
+        let (mut config, target_dir) = test_config("copy");
+        config.sandbox.file_size_mb = 1;
+        config.sandbox.allow_network = true;
+
+        let sandbox: Sandbox = Sandbox::create(&config).unwrap();
+        let copy_root: PathBuf = sandbox.project_path().parent().unwrap().to_path_buf();
+        assert!(sandbox.project_path().join("Cargo.toml").exists());
+        assert_ne!(sandbox.project_path(), config.web_server_project_path());
+
+        let output: std::process::Output = sandbox
+            .command("sh", SandboxStep::Build)
+            .args(["-c", "pwd; cat /proc/self/limits; env"])
+            .output()
+            .unwrap();
+        let output: String = String::from_utf8_lossy(&output.stdout).to_string();
+        assert!(output.starts_with(&sandbox.project_path().display().to_string()));
+        assert!(output.contains("Max file size             1048576"));
+        assert!(output.contains("CARGO_TARGET_DIR="));
+        assert!(!output.contains("CARGO_NET_OFFLINE"));
+
+        drop(sandbox);
+        assert!(!copy_root.exists());
+        let _ = fs::remove_dir_all(&target_dir);
+    }
+
+    #[test]
+    fn tests_sandbox_build_has_no_network() {
+        let (mut config, target_dir) = test_config("network");
+        config.sandbox.env_allowlist = vec!["PATH".to_string()];
+
+        let sandbox: Sandbox = Sandbox::create(&config).unwrap();
+        let read_interfaces = |step: SandboxStep| -> String {
+            let output: std::process::Output = sandbox
+                .command("sh", step)
+                .args(["-c", "cat /proc/net/dev; echo $CARGO_NET_OFFLINE"])
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).to_string()
+        };
+
+        let build: String = read_interfaces(SandboxStep::Build);
+        assert!(build.trim_end().ends_with("true"));
+        if *sandbox.isolation() != Isolation::LimitsOnly {
+            // Only the loopback interface of the new network namespace
+            assert_eq!(build.matches(':').count(), 1);
+        }
+        assert!(!read_interfaces(SandboxStep::Run)
+            .trim_end()
+            .ends_with("true"));
+
+        // The server keeps the network of the machine but not its user namespace
+        if *sandbox.isolation() == Isolation::Namespaces {
+            let output: std::process::Output = sandbox
+                .command("readlink", SandboxStep::Run)
+                .arg("/proc/self/ns/user")
+                .output()
+                .unwrap();
+            let user_namespace: PathBuf = fs::read_link("/proc/self/ns/user").unwrap();
+            assert_ne!(
+                String::from_utf8_lossy(&output.stdout).trim(),
+                user_namespace.display().to_string()
+            );
+        }
+
+        drop(sandbox);
+        let _ = fs::remove_dir_all(&target_dir);
+    }
+}
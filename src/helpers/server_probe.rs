@@ -0,0 +1,118 @@
+use crate::models::general::error::WizError;
+
+use std::process::Child;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time;
+
+// Pause between connection attempts
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+// How waiting for the generated server ended
+#[derive(Debug, PartialEq)]
+pub enum Readiness {
+    Ready,
+    // The process exited before the port accepted connections
+    Exited { code: Option<i32> },
+    TimedOut,
+}
+
+// Polls the port until it accepts connections, the process exits or the timeout is reached
+pub async fn wait_for_port(
+    process: &mut Child,
+    port: u16,
+    timeout: Duration,
+) -> Result<Readiness, WizError> {
+    let deadline: Instant = Instant::now() + timeout;
+
+    loop {
+        if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            return Ok(Readiness::Ready);
+        }
+
+        let exit_status: Option<std::process::ExitStatus> = process
+            .try_wait()
+            .map_err(|e| WizError::process("generated server", e))?;
+        if let Some(exit_status) = exit_status {
+            return Ok(Readiness::Exited {
+                code: exit_status.code(),
+            });
+        }
+
+        if Instant::now() >= deadline {
+            return Ok(Readiness::TimedOut);
+        }
+        time::sleep(PROBE_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::process::{Command, Stdio};
+
+    fn spawn_shell(script: &str) -> Child {
+        Command::new("sh")
+            .args(["-c", script])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    // Whatever an exited process wrote to its piped stdout and stderr
+    fn read_process_output(process: &mut Child) -> String {
+        let mut output: String = String::new();
+        if let Some(mut stdout) = process.stdout.take() {
+            let _ = stdout.read_to_string(&mut output);
+        }
+        if let Some(mut stderr) = process.stderr.take() {
+            let _ = stderr.read_to_string(&mut output);
+        }
+        output
+    }
+
+    // A port nothing listens on
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[tokio::test]
+    async fn tests_wait_for_port() {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut process: Child = spawn_shell("sleep 5");
+        let readiness: Readiness = wait_for_port(
+            &mut process,
+            listener.local_addr().unwrap().port(),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(readiness, Readiness::Ready);
+
+        let readiness: Readiness =
+            wait_for_port(&mut process, free_port(), Duration::from_millis(300))
+                .await
+                .unwrap();
+        assert_eq!(readiness, Readiness::TimedOut);
+        let _ = process.kill();
+        let _ = process.wait();
+
+        let mut process: Child =
+            spawn_shell("echo 'error[E0425]: cannot find value' >&2; exit 101");
+        let readiness: Readiness = wait_for_port(&mut process, free_port(), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(readiness, Readiness::Exited { code: Some(101) });
+        assert_eq!(
+            read_process_output(&mut process),
+            "error[E0425]: cannot find value\n"
+        );
+    }
+}
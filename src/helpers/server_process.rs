@@ -0,0 +1,218 @@
+use crate::helpers::config::WizConfig;
+use crate::helpers::process_output::OutputDrain;
+use crate::helpers::sandbox::{Sandbox, SandboxStep};
+use crate::models::general::error::WizError;
+
+use std::fs;
+use std::io;
+use std::net::TcpListener;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Process groups of the servers which are running, killed on Ctrl-C
+static RUNNING_GROUPS: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+// How long stop waits for the last output after killing the server
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Generated server started from its built binary in its own process group
+// The whole group is killed when the handle is stopped or dropped, also while unwinding a panic
+// Its stderr is read on a background thread, so a chatty server never blocks on a full pipe
+#[derive(Debug)]
+pub struct ServerProcess {
+    child: Child,
+    stopped: bool,
+    stderr: OutputDrain,
+}
+
+impl ServerProcess {
+    // Runs the binary built by `cargo build` in the sandbox, failing fast when the port is taken
+    pub fn start(config: &WizConfig, sandbox: &Sandbox) -> Result<Self, WizError> {
+        let port: u16 = config.server.port;
+        if is_port_in_use(port) {
+            return Err(WizError::PortInUse { port });
+        }
+
+        let binary: PathBuf = server_binary_path(sandbox)?;
+        Self::spawn(sandbox.command(&binary, SandboxStep::Run))
+    }
+
+    fn spawn(mut command: Command) -> Result<Self, WizError> {
+        let program: String = command.get_program().to_string_lossy().to_string();
+        let mut child: Child = command
+            .process_group(0)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| WizError::process(&program, e))?;
+
+        RUNNING_GROUPS.lock().unwrap().push(child.id() as i32);
+        let stderr: OutputDrain = OutputDrain::spawn(child.stderr.take());
+        Ok(Self {
+            child,
+            stopped: false,
+            stderr,
+        })
+    }
+
+    pub fn process_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    // Kills the process group and returns what the server wrote to stderr
+    pub fn stop(mut self) -> String {
+        self.kill_group();
+
+        // The pipe closes once the group is gone, unless the server detached a process holding it
+        self.stderr.finish(STDERR_DRAIN_TIMEOUT)
+    }
+
+    fn kill_group(&mut self) {
+        if self.stopped {
+            return;
+        }
+        self.stopped = true;
+
+        let group: i32 = self.child.id() as i32;
+        kill_process_group(group);
+        let _ = self.child.wait();
+        RUNNING_GROUPS
+            .lock()
+            .unwrap()
+            .retain(|running: &i32| *running != group);
+    }
+}
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        self.kill_group();
+    }
+}
+
+// Kills every server still running, i.e. when the user presses Ctrl-C
+pub fn kill_running_servers() {
+    let groups: Vec<i32> = RUNNING_GROUPS
+        .lock()
+        .map(|groups| groups.clone())
+        .unwrap_or_default();
+    for group in groups {
+        kill_process_group(group);
+    }
+}
+
+fn kill_process_group(group: i32) {
+    // SAFETY: killpg only sends a signal, the group was created for the server
+    unsafe {
+        libc::killpg(group, libc::SIGKILL);
+    }
+}
+
+pub fn is_port_in_use(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_err()
+}
+
+// target/debug/<package name> of the web server project built in the sandbox
+pub fn server_binary_path(sandbox: &Sandbox) -> Result<PathBuf, WizError> {
+    let manifest_path: PathBuf = sandbox.project_path().join("Cargo.toml");
+    let manifest: String = fs::read_to_string(&manifest_path)
+        .map_err(|e| WizError::file_io(manifest_path.display(), e))?;
+    let package_name: String = manifest
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|manifest: toml::Table| {
+            manifest
+                .get("package")?
+                .get("name")?
+                .as_str()
+                .map(|name: &str| name.to_string())
+        })
+        .ok_or_else(|| WizError::Config {
+            message: format!("{} has no package name", manifest_path.display()),
+        })?;
+
+    let binary: PathBuf = sandbox.target_path().join("debug").join(package_name);
+    if !binary.exists() {
+        return Err(WizError::file_io(
+            binary.display(),
+            io::Error::new(io::ErrorKind::NotFound, "build the server first"),
+        ));
+    }
+    Ok(binary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::process_output::OUTPUT_TAIL_BYTES;
+    use std::time::Instant;
+
+    // Zombies waiting to be reaped count as dead
+    fn is_process_alive(pid: i32) -> bool {
+        fs::read_to_string(format!("/proc/{}/stat", pid))
+            .map(|stat: String| !stat.contains(") Z "))
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn tests_drop_kills_the_process_group() {
+        // The shell leaves a grandchild behind, like a server spawning helper processes
+        let mut command: Command = Command::new("sh");
+        command.args(["-c", "sleep 30 & echo $! >&2; wait"]);
+        let server: ServerProcess = ServerProcess::spawn(command).unwrap();
+
+        let deadline: Instant = Instant::now() + Duration::from_secs(5);
+        while !server.stderr.text().contains('\n') && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let grandchild: i32 = server.stderr.text().trim().parse().unwrap();
+        assert!(is_process_alive(grandchild));
+
+        drop(server);
+
+        // SIGKILL is delivered asynchronously, give it a moment
+        let deadline: Instant = Instant::now() + Duration::from_secs(5);
+        while is_process_alive(grandchild) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(!is_process_alive(grandchild));
+        assert!(RUNNING_GROUPS.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn tests_stderr_is_drained_while_running() {
+        // Far more than a pipe buffer: the server blocks unless stderr is read while it runs
+        let mut command: Command = Command::new("sh");
+        command.args([
+            "-c",
+            "head -c 300000 /dev/zero | tr '\\0' x >&2; echo listening >&2; sleep 30",
+        ]);
+        let server: ServerProcess = ServerProcess::spawn(command).unwrap();
+
+        let deadline: Instant = Instant::now() + Duration::from_secs(5);
+        while !server.stderr.text().ends_with("listening\n") && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let output: String = server.stop();
+        assert!(output.starts_with("[earlier output dropped]\n"));
+        assert!(output.ends_with("xxxlistening\n"));
+        assert!(output.len() <= OUTPUT_TAIL_BYTES + 32);
+    }
+
+    #[test]
+    fn tests_port_in_use_fails_fast() {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut config: WizConfig = WizConfig::resolve(None).expect("Failed to load wizai.toml");
+        config.sandbox.enabled = false;
+        config.server.port = listener.local_addr().unwrap().port();
+
+        let sandbox: Sandbox = Sandbox::create(&config).unwrap();
+        match ServerProcess::start(&config, &sandbox) {
+            Err(WizError::PortInUse { port }) => assert_eq!(port, config.server.port),
+            other => panic!("Expected the port to be in use, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::helpers::approval::{ApprovalHook, ApprovalRequest};
+use crate::helpers::config::WizConfig;
+use crate::helpers::general::copy_project_template;
+
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+
+// The repository's wizai.toml with every output in a fresh temporary workspace
+// Templates are still read from the repository; `name` keeps tests running in parallel apart
+pub fn temp_workspace_config(name: &str) -> (PathBuf, WizConfig) {
+    let output_dir: PathBuf = env::temp_dir().join(format!("wizai_{}_{}", name, process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    fs::create_dir_all(&output_dir).expect("Failed to create temporary workspace");
+
+    let config: WizConfig = WizConfig::resolve(None)
+        .expect("Failed to load wizai.toml")
+        .with_output_dir(&output_dir);
+    (output_dir, config)
+}
+
+// A temporary workspace holding a copy of the repository's server project
+pub fn temp_server_workspace(name: &str) -> (PathBuf, WizConfig) {
+    let (output_dir, config) = temp_workspace_config(name);
+    let repo_config: WizConfig = WizConfig::resolve(None).expect("Failed to load wizai.toml");
+    copy_project_template(
+        &repo_config.web_server_project_path(),
+        &config.web_server_project_path(),
+    )
+    .expect("Failed to copy the server project");
+    (output_dir, config)
+}
+
+// Approves everything without asking on the terminal
+#[derive(Debug)]
+pub struct ApproveAll;
+
+impl ApprovalHook for ApproveAll {
+    fn approve(&self, _request: &ApprovalRequest) -> bool {
+        true
+    }
+}
+
+// Local listener answering every request with the given status, i.e. for URL checks
+// Returns its base URL, the listener lives until the test process exits
+pub fn serve_status(status_code: u16) -> String {
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url: String = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            answer(&mut stream, status_code);
+        }
+    });
+    url
+}
+
+fn answer(stream: &mut TcpStream, status_code: u16) {
+    let mut request: [u8; 4096] = [0; 4096];
+    let _ = stream.read(&mut request);
+    let response: String = format!(
+        "HTTP/1.1 {} Test\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        status_code
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
@@ -8,21 +8,266 @@ macro_rules! get_function_string {
 #[macro_use]
 mod ai_functions;
 mod apis;
+mod cli;
 mod helpers;
 mod models;
 
+use apis::call_request::OpenAiProvider;
+use apis::cassette::{CassetteMode, CassetteProvider};
+use apis::llm_provider::LlmProvider;
+use apis::mock_provider::MockProvider;
+use apis::retry::RetryProvider;
+use clap::Parser;
+use cli::{Cli, Command, PromptArgs};
+use helpers::approval::{ApprovalHook, ApprovalPolicy};
 use helpers::command_line::get_user_response;
+use helpers::config::WizConfig;
+use helpers::general::{
+    copy_project_template, read_api_endpoints, read_checkpoint, save_checkpoint,
+};
+use helpers::sandbox::Sandbox;
+use helpers::server_process::kill_running_servers;
+use models::agents::agent_backend::{AgentBackendDeveloper, EndpointTestReport};
+use models::agents::agent_traits::RouteObject;
+use models::agents_manager::checkpoint::Checkpoint;
 use models::agents_manager::managing_agent::ManagingAgent;
+use models::general::error::WizError;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Select the LLM provider from the environment
+// WIZAI_CASSETTE_MODE=replay serves a previous recording, WIZAI_MOCK_FIXTURES answers from fixture files,
+// otherwise OpenAI is called (and recorded when WIZAI_CASSETTE_MODE=record)
+fn build_provider(
+    config: &WizConfig,
+) -> Result<(Arc<dyn LlmProvider>, Option<OpenAiProvider>), WizError> {
+    let cassette: Option<(PathBuf, CassetteMode)> = match env::var("WIZAI_CASSETTE") {
+        Ok(path) if !path.is_empty() => {
+            let mode: String =
+                env::var("WIZAI_CASSETTE_MODE").unwrap_or_else(|_| "record".to_string());
+            let mode: CassetteMode =
+                CassetteMode::parse(&mode).ok_or_else(|| WizError::Config {
+                    message: "WIZAI_CASSETTE_MODE must be either 'record' or 'replay'".to_string(),
+                })?;
+            Some((PathBuf::from(path), mode))
+        }
+        _ => None,
+    };
+
+    if let Some((path, CassetteMode::Replay)) = &cassette {
+        let player: CassetteProvider = CassetteProvider::replay(path)
+            .map_err(|e: io::Error| WizError::file_io(path.display(), e))?;
+        return Ok((Arc::new(player), None));
+    }
+
+    // Offline runs: answer every prompt from fixture files instead of OpenAI
+    let (provider, openai): (Arc<dyn LlmProvider>, Option<OpenAiProvider>) =
+        match env::var("WIZAI_MOCK_FIXTURES") {
+            Ok(dir) if !dir.is_empty() => {
+                let mock: MockProvider = MockProvider::from_fixture_dir(Path::new(&dir))
+                    .map_err(|e: io::Error| WizError::file_io(&dir, e))?;
+                (Arc::new(mock), None)
+            }
+            _ => {
+                let mut openai: OpenAiProvider =
+                    OpenAiProvider::from_env()?.with_temperature(config.llm.temperature);
+                if let Some(model) = &config.llm.model {
+                    openai = openai.with_model(model);
+                }
+                let retrying: RetryProvider =
+                    RetryProvider::new(Arc::new(openai.clone()), config.retry_policy());
+                (Arc::new(retrying), Some(openai))
+            }
+        };
+
+    match cassette {
+        Some((path, _)) => Ok((Arc::new(CassetteProvider::record(&path, provider)), openai)),
+        None => Ok((provider, openai)),
+    }
+}
+
+// Provider for every agent, honouring the per agent models of [llm.agent_models]
+fn build_agent_providers(
+    config: &WizConfig,
+    openai: &Option<OpenAiProvider>,
+) -> HashMap<String, Arc<dyn LlmProvider>> {
+    let mut agent_providers: HashMap<String, Arc<dyn LlmProvider>> = HashMap::new();
+    if let Some(openai) = openai {
+        for (position, model) in &config.llm.agent_models {
+            let agent_provider: OpenAiProvider = openai.clone().with_model(model);
+            let retrying: RetryProvider =
+                RetryProvider::new(Arc::new(agent_provider), config.retry_policy());
+            agent_providers.insert(position.clone(), Arc::new(retrying));
+        }
+    }
+    agent_providers
+}
+
+// Prompt from the command line, a prompt file or stdin
+fn read_prompt(args: &PromptArgs, non_interactive: bool) -> Result<String, WizError> {
+    if let Some(prompt) = &args.prompt {
+        return Ok(prompt.clone());
+    }
+    if let Some(path) = &args.prompt_file {
+        let prompt: String =
+            fs::read_to_string(path).map_err(|e| WizError::file_io(path.display(), e))?;
+        return Ok(prompt.trim().to_string());
+    }
+    if non_interactive {
+        return Err(WizError::Config {
+            message: "a prompt or --prompt-file is required in non-interactive mode".to_string(),
+        });
+    }
+    Ok(get_user_response("What website are we building today?"))
+}
+
+async fn run(cli: Cli) -> Result<(), WizError> {
+    let mut config: WizConfig = WizConfig::resolve(cli.config.as_deref())?;
+    if let Some(output_dir) = &cli.output_dir {
+        let template_project: PathBuf = config.web_server_project_path();
+        config = config.with_output_dir(output_dir);
+
+        let server_project: PathBuf = config.web_server_project_path();
+        if !server_project.exists() {
+            copy_project_template(&template_project, &server_project)?;
+        }
+    }
+
+    let mut approval_policy: ApprovalPolicy = cli.approval.unwrap_or(config.approval.policy);
+    if cli.non_interactive {
+        approval_policy = approval_policy.non_interactive();
+    }
+    let approval: Arc<dyn ApprovalHook> = approval_policy.hook();
+
+    let (provider, openai): (Arc<dyn LlmProvider>, Option<OpenAiProvider>) =
+        build_provider(&config)?;
+    let agent_providers: HashMap<String, Arc<dyn LlmProvider>> =
+        build_agent_providers(&config, &openai);
+    let config: Arc<WizConfig> = Arc::new(config);
+
+    let mut manage_agent: ManagingAgent = match &cli.command {
+        Command::New(args) | Command::Plan(args) if cli.resume => {
+            if args.prompt.is_some() || args.prompt_file.is_some() {
+                return Err(WizError::Config {
+                    message: "--resume continues the saved run and takes no prompt".to_string(),
+                });
+            }
+            let checkpoint: Checkpoint = read_checkpoint(&config)?;
+            ManagingAgent::from_checkpoint(checkpoint, provider, config.clone())
+        }
+        Command::New(args) | Command::Plan(args) => {
+            let usr_req: String = read_prompt(args, cli.non_interactive)?;
+            ManagingAgent::new(usr_req, provider, config.clone()).await?
+        }
+        Command::Build => {
+            let checkpoint: Checkpoint = read_checkpoint(&config)?;
+            if cli.resume {
+                ManagingAgent::from_checkpoint(checkpoint, provider, config.clone())
+            } else {
+                ManagingAgent::from_factsheet(checkpoint.factsheet, provider, config.clone())
+            }
+        }
+        Command::Test | Command::Schema => {
+            let backend_provider: Arc<dyn LlmProvider> =
+                match agent_providers.get(AgentBackendDeveloper::POSITION) {
+                    Some(agent_provider) => agent_provider.clone(),
+                    None => provider,
+                };
+            let backend: AgentBackendDeveloper =
+                AgentBackendDeveloper::new(backend_provider, config.clone())
+                    .with_approval(approval);
+            return run_backend_checks(&cli.command, &config, &backend).await;
+        }
+    };
+
+    manage_agent.set_approval_hook(approval);
+    manage_agent.set_checkpointing(true);
+    for (position, agent_provider) in agent_providers {
+        manage_agent.set_agent_provider(&position, agent_provider);
+    }
+
+    let project_res: Result<(), WizError> = match &cli.command {
+        Command::Plan(_) => manage_agent.execute_plan().await,
+        Command::Build => manage_agent.execute_build().await,
+        _ => manage_agent.execute_project().await,
+    };
+
+    // Keep the checkpoint, even of a failed or declined run, for a later build or --resume
+    save_checkpoint(&config, manage_agent.checkpoint())?;
+    if project_res.is_err() {
+        println!(
+            "Progress saved to {}, continue with --resume",
+            config.checkpoint_path().display()
+        );
+    }
+    project_res
+}
+
+// test and schema work on the server generated by a previous run
+async fn run_backend_checks(
+    command: &Command,
+    config: &WizConfig,
+    backend: &AgentBackendDeveloper,
+) -> Result<(), WizError> {
+    match command {
+        Command::Schema => {
+            let api_endpoints: Vec<RouteObject> = backend.extract_api_schema().await?;
+            println!(
+                "Extracted {} endpoints, OpenAPI document written to {}",
+                api_endpoints.len(),
+                config.openapi_path().display()
+            );
+            Ok(())
+        }
+        _ => {
+            let api_endpoints: Vec<RouteObject> = read_api_endpoints(config)?;
+
+            backend.confirm_run_generated_code()?;
+            let sandbox: Sandbox = backend.create_sandbox()?;
+            if let Err(errors) = backend.build_server(&sandbox)? {
+                return Err(WizError::BuildFailure {
+                    attempts: 1,
+                    errors,
+                });
+            }
+            let report: EndpointTestReport =
+                backend.test_endpoints(&sandbox, &api_endpoints).await?;
+            if report.failures.is_empty() {
+                Ok(())
+            } else {
+                Err(WizError::EndpointTests {
+                    failed_routes: report.failures,
+                })
+            }
+        }
+    }
+}
+
+// Exit code of a run stopped by a declined approval, so CI does not mistake it for a success
+const EXIT_USER_ABORT: i32 = 3;
 
 #[tokio::main]
 async fn main() {
-    let usr_req: String = get_user_response("What website are we building today?");
-
-    let mut manage_agent: ManagingAgent = ManagingAgent::new(usr_req)
-        .await
-        .expect("Error creating agent");
+    let cli: Cli = Cli::parse();
 
-    manage_agent.execute_project().await;
+    // Generated servers run in their own process group and do not see the terminal's Ctrl-C
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            kill_running_servers();
+            eprintln!("Interrupted");
+            std::process::exit(130);
+        }
+    });
 
-    dbg!(manage_agent);
+    if let Err(e) = run(cli).await {
+        eprintln!("{}", e);
+        if e.is_user_abort() {
+            std::process::exit(EXIT_USER_ABORT);
+        }
+        std::process::exit(1);
+    }
 }
@@ -1,7 +1,7 @@
 use crate::models::agent_basic::basic_traits::BasicTraits;
-use crate::models::general::llm::MessageAI;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum AgentState {
     Discovery,
     Working,
@@ -14,19 +14,9 @@ pub struct BasicAgent {
     pub objective: String,
     pub position: String,
     pub state: AgentState,
-    pub memory: Vec<MessageAI>,
 }
 
 impl BasicTraits for BasicAgent {
-    fn new(objective: String, position: String) -> Self {
-        Self {
-            objective,
-            position,
-            state: AgentState::Discovery,
-            memory: Vec::from([]),
-        }
-    }
-
     fn update_state(&mut self, new_state: AgentState) {
         self.state = new_state;
     }
@@ -42,8 +32,4 @@ impl BasicTraits for BasicAgent {
     fn get_state(&self) -> &AgentState {
         &self.state
     }
-
-    fn get_memory(&self) -> &Vec<MessageAI> {
-        &self.memory
-    }
 }
@@ -1,48 +1,62 @@
 use crate::ai_functions::aifunc_architect::{print_project_scope, print_site_urls};
+use crate::apis::llm_provider::LlmProvider;
 use crate::helpers::command_line::PrintCommand;
-use crate::helpers::general::{ai_task_request_decoded, check_status_code};
+use crate::helpers::general::{ai_task_request_decoded, check_status_code, http_client};
 use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
 use crate::models::agent_basic::basic_traits::BasicTraits;
-use crate::models::agents::agent_traits::{FactSheet, ProjectScope, SpecialFunctions};
+use crate::models::agents::agent_traits::{
+    FactSheet, FactSheetField, ProjectScope, SpecialFunctions,
+};
+use crate::models::general::error::WizError;
 
 use async_trait::async_trait;
 use reqwest::Client;
+use std::sync::Arc;
 use std::time::Duration;
 
 // Solutions Architect
 #[derive(Debug)]
 pub struct AgentSolutionArchitect {
     attributes: BasicAgent,
+    provider: Arc<dyn LlmProvider>,
 }
 
 impl AgentSolutionArchitect {
-    pub fn new() -> Self {
+    pub const POSITION: &'static str = "Solutions Architect";
+
+    pub fn new(provider: Arc<dyn LlmProvider>) -> Self {
         let attributes: BasicAgent = BasicAgent {
             objective: "Gathers information and design solutions for website development"
                 .to_string(),
-            position: "Solutions Architect".to_string(),
+            position: Self::POSITION.to_string(),
             state: AgentState::Discovery,
-            memory: vec![],
         };
 
-        Self { attributes }
+        Self {
+            attributes,
+            provider,
+        }
     }
 
     // Retrieve Project Scope
-    async fn call_project_scope(&mut self, factsheet: &mut FactSheet) -> ProjectScope {
-        let msg_context: String = format!("{}", factsheet.project_description);
+    async fn call_project_scope(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<ProjectScope, WizError> {
+        let msg_context: String = factsheet.project_description.to_string();
 
         let ai_response: ProjectScope = ai_task_request_decoded::<ProjectScope>(
+            self.provider.as_ref(),
             msg_context,
             &self.attributes.position,
             get_function_string!(print_project_scope),
             print_project_scope,
         )
-        .await;
+        .await?;
 
-        factsheet.project_scope = Some(ai_response.clone());
+        factsheet.project_scope = Some(ai_response);
         self.attributes.update_state(AgentState::Finished);
-        return ai_response;
+        Ok(ai_response)
     }
 
     // Retrieve Project Scope
@@ -50,17 +64,19 @@ impl AgentSolutionArchitect {
         &mut self,
         factsheet: &mut FactSheet,
         msg_context: String,
-    ) {
+    ) -> Result<(), WizError> {
         let ai_response: Vec<String> = ai_task_request_decoded::<Vec<String>>(
+            self.provider.as_ref(),
             msg_context,
             &self.attributes.position,
             get_function_string!(print_site_urls),
             print_site_urls,
         )
-        .await;
+        .await?;
 
         factsheet.external_urls = Some(ai_response);
         self.attributes.state = AgentState::UnitTesting;
+        Ok(())
     }
 }
 
@@ -70,15 +86,24 @@ impl SpecialFunctions for AgentSolutionArchitect {
         &self.attributes
     }
 
-    async fn execute(
-        &mut self,
-        factsheet: &mut FactSheet,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_agent_state(&mut self, state: AgentState) {
+        self.attributes.state = state;
+    }
+
+    fn requires(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::ProjectDescription]
+    }
+
+    fn produces(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::ProjectScope, FactSheetField::ExternalUrls]
+    }
+
+    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
         // !!! WARNING - BE CAREFUL OF INFINITATE LOOPS !!!
         while self.attributes.state != AgentState::Finished {
             match self.attributes.state {
                 AgentState::Discovery => {
-                    let project_scope: ProjectScope = self.call_project_scope(factsheet).await;
+                    let project_scope: ProjectScope = self.call_project_scope(factsheet).await?;
 
                     // Confirm if external urls
                     if project_scope.is_external_urls_required {
@@ -86,7 +111,7 @@ impl SpecialFunctions for AgentSolutionArchitect {
                             factsheet,
                             factsheet.project_description.clone(),
                         )
-                        .await;
+                        .await?;
                         self.attributes.state = AgentState::UnitTesting;
                     }
                 }
@@ -94,19 +119,13 @@ impl SpecialFunctions for AgentSolutionArchitect {
                 AgentState::UnitTesting => {
                     let mut exclude_urls: Vec<String> = vec![];
 
-                    let client: Client = Client::builder()
-                        .timeout(Duration::from_secs(5))
-                        .build()
-                        .unwrap();
+                    let client: Client = http_client(Duration::from_secs(5))?;
 
-                    // Defining urls to check
-                    let urls: &Vec<String> = factsheet
-                        .external_urls
-                        .as_ref()
-                        .expect("No URL object on factsheet");
+                    // Defining urls to check, none when the model listed no urls
+                    let urls: Vec<String> = factsheet.external_urls.clone().unwrap_or_default();
 
                     // Find faulty urls
-                    for url in urls {
+                    for url in &urls {
                         let endpoint_str: String = format!("Testing URL Endpoint: {}", url);
                         PrintCommand::UnitTest.print_agent_message(
                             self.attributes.position.as_str(),
@@ -125,13 +144,10 @@ impl SpecialFunctions for AgentSolutionArchitect {
                     }
 
                     // Exclude any faulty urls
-                    if exclude_urls.len() > 0 {
-                        let new_urls: Vec<String> = factsheet
-                            .external_urls
-                            .as_ref()
-                            .unwrap()
+                    if !exclude_urls.is_empty() {
+                        let new_urls: Vec<String> = urls
                             .iter()
-                            .filter(|url| !exclude_urls.contains(&url))
+                            .filter(|url| !exclude_urls.contains(url))
                             .cloned()
                             .collect();
                         factsheet.external_urls = Some(new_urls);
@@ -155,17 +171,26 @@ impl SpecialFunctions for AgentSolutionArchitect {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::helpers::test_support::serve_status;
 
     #[tokio::test]
     async fn tests_solution_architect() {
-        let mut agent: AgentSolutionArchitect = AgentSolutionArchitect::new();
+        // The urls are checked against local listeners, never the internet
+        let available_url: String = format!("{}/api/prices", serve_status(200));
+        let missing_url: String = format!("{}/api/rates", serve_status(404));
+        let site_urls: String = serde_json::to_string(&[&available_url, &missing_url]).unwrap();
+        let provider: MockProvider = MockProvider::new()
+            .with_response(
+                "print_project_scope",
+                include_str!("../../../fixtures/llm/print_project_scope.txt"),
+            )
+            .with_response("print_site_urls", &site_urls);
+        let mut agent: AgentSolutionArchitect = AgentSolutionArchitect::new(Arc::new(provider));
 
         let mut factsheet: FactSheet = FactSheet {
             project_description: "Build a full stack website with user login and logout that shows latest crypto currency prices".to_string(),
-            project_scope: None,
-            external_urls: None,
-            backend_code: None,
-            api_endpoint_schema: None,
+            ..Default::default()
         };
 
         agent
@@ -173,7 +198,7 @@ mod tests {
             .await
             .expect("Unable to execute Solutions Architect Agent");
         assert_ne!(factsheet.project_scope, None);
-        assert!(factsheet.external_urls.is_some());
+        assert_eq!(factsheet.external_urls, Some(vec![available_url]));
 
         dbg!(factsheet);
     }
@@ -2,85 +2,199 @@ use crate::ai_functions::aifunc_backend::{
     print_backend_webserver_code, print_fixed_code, print_improved_webserver_code,
     print_rest_api_endpoints,
 };
+use crate::apis::llm_provider::LlmProvider;
+use crate::helpers::config::WizConfig;
 use crate::helpers::general::{
-    check_status_code, read_code_template_contents, read_exec_main_contents, save_api_endpoints,
-    save_backend_code, WEB_SERVER_PROJECT_PATH,
+    declare_module, read_code_template_contents, read_exec_main_contents, save_api_endpoints,
+    save_backend_code, save_openapi_document,
 };
 
-use crate::helpers::command_line::{confirm_safe_code, PrintCommand};
-use crate::helpers::general::ai_task_request;
+use crate::helpers::approval::{ApprovalHook, ApprovalRequest, TerminalApproval};
+use crate::helpers::code_fence::{extract_code, RUST_TAGS};
+use crate::helpers::command_line::PrintCommand;
+use crate::helpers::general::{ai_task_request, ai_task_request_decoded, http_client};
+use crate::helpers::openapi::{openapi_document, validate_openapi};
+use crate::helpers::request_synthesizer::RequestSynthesizer;
+use crate::helpers::route_extractor::{extract_routes, fill_unknown_types, route_disagreements};
+use crate::helpers::sandbox::{Sandbox, SandboxStep};
+use crate::helpers::server_probe::{wait_for_port, Readiness};
+use crate::helpers::server_process::ServerProcess;
 use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
-use crate::models::agents::agent_traits::{FactSheet, RouteObject, SpecialFunctions};
+use crate::models::agents::agent_layer::BackendLayer;
+use crate::models::agents::agent_traits::{
+    FactSheet, FactSheetField, RouteObject, SpecialFunctions,
+};
+use crate::models::general::error::WizError;
 
 use async_trait::async_trait;
 use reqwest::Client;
-use std::process::{Command, Stdio};
+use serde_json::Value;
+use std::process::Stdio;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time;
+
+// Lines of the server stderr sent to the fix step
+const SERVER_LOG_LINES: usize = 40;
+
+// Endpoint failures of one test run together with what the server printed to stderr
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EndpointTestReport {
+    pub failures: Vec<String>,
+    pub server_stderr: String,
+}
+
+impl EndpointTestReport {
+    // Bug description for the fix step, only the end of the server log is kept
+    pub fn bug_errors(&self) -> String {
+        let log_lines: Vec<&str> = self.server_stderr.lines().collect();
+        let log_tail: String =
+            log_lines[log_lines.len().saturating_sub(SERVER_LOG_LINES)..].join("\n");
+        format!(
+            "RUNTIME_FAILURES: the server builds, but these requests failed:\n{}\nSERVER_STDERR:\n{}",
+            self.failures.join("\n"),
+            log_tail
+        )
+    }
+}
 
 #[derive(Debug)]
 pub struct AgentBackendDeveloper {
     attributes: BasicAgent,
     bug_errors: Option<String>,
     bug_count: u8,
+    runtime_fix_count: u8,
+    provider: Arc<dyn LlmProvider>,
+    config: Arc<WizConfig>,
+    approval: Arc<dyn ApprovalHook>,
+    layers: Vec<BackendLayer>,
 }
 
 impl AgentBackendDeveloper {
-    pub fn new() -> Self {
+    pub const POSITION: &'static str = "Backend Developer";
+
+    pub fn new(provider: Arc<dyn LlmProvider>, config: Arc<WizConfig>) -> Self {
         let attributes: BasicAgent = BasicAgent {
             objective: "Develops backend code for webserver and json database".to_string(),
-            position: "Backend Developer".to_string(),
+            position: Self::POSITION.to_string(),
             state: AgentState::Discovery,
-            memory: vec![],
         };
 
         Self {
             attributes,
             bug_errors: None,
             bug_count: 0,
+            runtime_fix_count: 0,
+            provider,
+            config,
+            approval: Arc::new(TerminalApproval),
+            layers: vec![],
         }
     }
 
-    async fn call_initial_backend_code(&mut self, factsheet: &mut FactSheet) {
-        let code_template_str: String = read_code_template_contents();
+    // Decides whether the generated code may be built and run
+    pub fn with_approval(mut self, approval: Arc<dyn ApprovalHook>) -> Self {
+        self.approval = approval;
+        self
+    }
+
+    // Modules written by the layer developers which main.rs has to use
+    pub fn with_layers(mut self, layers: Vec<BackendLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    // Scope flags and the planned layer modules for the initial prompt
+    fn scope_context(&self, factsheet: &FactSheet) -> String {
+        let mut context: String = match &factsheet.project_scope {
+            Some(scope) => format!(
+                "PROJECT_SCOPE: crud required: {}, user login and logout: {}, external urls: {} \n",
+                scope.is_crud_required,
+                scope.is_user_login_and_logout,
+                scope.is_external_urls_required
+            ),
+            None => String::new(),
+        };
+
+        for layer in &self.layers {
+            if let Some(code) = layer.code(factsheet) {
+                context.push_str(&format!(
+                    "MODULE src/{}.rs (already written, declare it with `mod {};` and use it instead of rewriting it): {} \n",
+                    layer.module_name(),
+                    layer.module_name(),
+                    code
+                ));
+            }
+        }
+        context
+    }
+
+    // Saves main.rs with every planned layer module declared
+    fn store_backend_code(
+        &self,
+        factsheet: &mut FactSheet,
+        ai_response: &str,
+    ) -> Result<(), WizError> {
+        let mut backend_code: String = extract_code(ai_response, &RUST_TAGS);
+        for layer in &self.layers {
+            if let Some(declared_code) = declare_module(&backend_code, layer.module_name()) {
+                backend_code = declared_code;
+            }
+        }
+
+        save_backend_code(&self.config, &backend_code)?;
+        factsheet.backend_code = Some(backend_code);
+        Ok(())
+    }
+
+    async fn call_initial_backend_code(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), WizError> {
+        let code_template_str: String = read_code_template_contents(&self.config)?;
 
         // Concatenate Instruction
         let msg_context: String = format!(
-            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n",
-            code_template_str, factsheet.project_description
+            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n {}SERVER_ADDRESS: the server must bind to 127.0.0.1:{} \n",
+            code_template_str,
+            factsheet.project_description,
+            self.scope_context(factsheet),
+            self.config.server.port
         );
 
         let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
             msg_context,
             &self.attributes.position,
             get_function_string!(print_backend_webserver_code),
             print_backend_webserver_code,
         )
-        .await;
+        .await?;
 
-        save_backend_code(&ai_response);
-        factsheet.backend_code = Some(ai_response);
+        self.store_backend_code(factsheet, &ai_response)
     }
 
-    async fn call_improved_backend_code(&mut self, factsheet: &mut FactSheet) {
+    async fn call_improved_backend_code(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), WizError> {
         let msg_context: String = format!(
             "CODE TEMPLATE: {:?} \n PROJECT_DESCRIPTION: {:?} \n",
             factsheet.backend_code, factsheet
         );
 
         let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
             msg_context,
             &self.attributes.position,
             get_function_string!(print_improved_webserver_code),
             print_improved_webserver_code,
         )
-        .await;
+        .await?;
 
-        save_backend_code(&ai_response);
-        factsheet.backend_code = Some(ai_response);
+        self.store_backend_code(factsheet, &ai_response)
     }
 
-    async fn call_fix_code_bugs(&mut self, factsheet: &mut FactSheet) {
+    async fn call_fix_code_bugs(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
         let msg_context: String = format!(
             "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n
       THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
@@ -88,32 +202,254 @@ impl AgentBackendDeveloper {
         );
 
         let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
             msg_context,
             &self.attributes.position,
             get_function_string!(print_fixed_code),
             print_fixed_code,
         )
-        .await;
+        .await?;
 
-        save_backend_code(&ai_response);
-        factsheet.backend_code = Some(ai_response);
+        self.store_backend_code(factsheet, &ai_response)
     }
 
-    async fn call_extract_rest_api_endpoints(&self) -> String {
-        let backend_code: String = read_exec_main_contents();
+    // Endpoints as read by the model, an answer which stays invalid after the repairs is a
+    // Decode error
+    async fn call_extract_rest_api_endpoints(&self) -> Result<Vec<RouteObject>, WizError> {
+        let backend_code: String = read_exec_main_contents(&self.config)?;
 
         // Structure message context
         let msg_context: String = format!("CODE_INPUT: {}", backend_code);
 
-        let ai_response: String = ai_task_request(
+        ai_task_request_decoded::<Vec<RouteObject>>(
+            self.provider.as_ref(),
             msg_context,
             &self.attributes.position,
             get_function_string!(print_rest_api_endpoints),
             print_rest_api_endpoints,
         )
-        .await;
+        .await
+    }
 
-        ai_response
+    // Copy of the web server project the generated code is built and run in
+    pub fn create_sandbox(&self) -> Result<Sandbox, WizError> {
+        let sandbox: Sandbox = Sandbox::create(&self.config)?;
+        let sandbox_msg: String = format!(
+            "Backend Code Unit Testing: Sandbox in {} ({:?})",
+            sandbox.project_path().display(),
+            sandbox.isolation()
+        );
+        PrintCommand::UnitTest
+            .print_agent_message(self.attributes.position.as_str(), sandbox_msg.as_str());
+
+        sandbox.fetch_dependencies();
+        Ok(sandbox)
+    }
+
+    // Builds the web server in the sandbox, returns the compiler errors when it does not build
+    pub fn build_server(&self, sandbox: &Sandbox) -> Result<Result<(), String>, WizError> {
+        let build_backend_server: std::process::Output = sandbox
+            .command("cargo", SandboxStep::Build)
+            .arg("build")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| WizError::process("cargo build", e))?;
+
+        if build_backend_server.status.success() {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(
+                String::from_utf8_lossy(&build_backend_server.stderr).to_string()
+            ))
+        }
+    }
+
+    // Guard:: ENSURE AI SAFETY
+    pub fn confirm_run_generated_code(&self) -> Result<(), WizError> {
+        let request: ApprovalRequest = ApprovalRequest {
+            position: self.attributes.position.clone(),
+            action: "build and run the generated web server".to_string(),
+            project_path: self.config.web_server_project_path(),
+        };
+
+        if !self.approval.approve(&request) {
+            return Err(WizError::UserAbort {
+                reason: self.approval.decline_reason(),
+            });
+        }
+        Ok(())
+    }
+
+    // Read the endpoints of the generated server from its code and save them as the API schema
+    // The model reads them too: its answer fills the types the code does not tell and every
+    // disagreement is reported. Code which cannot be parsed falls back to the model alone
+    pub async fn extract_api_schema(&self) -> Result<Vec<RouteObject>, WizError> {
+        // Endpoints the model could not describe are not fatal while the code can be read
+        let reported: Result<Vec<RouteObject>, WizError> =
+            match self.call_extract_rest_api_endpoints().await {
+                Err(e @ WizError::Decode { .. }) => Err(e),
+                other => Ok(other?),
+            };
+
+        let position: &str = self.attributes.position.as_str();
+        let mut api_endpoints: Vec<RouteObject> =
+            match (extract_routes(&self.config.exec_main_path())?, reported) {
+                (Ok(mut extracted), reported) if !extracted.is_empty() => {
+                    let reported: Vec<RouteObject> = reported.unwrap_or_else(|e| {
+                        PrintCommand::Issue.print_agent_message(position, &e.to_string());
+                        vec![]
+                    });
+                    for disagreement in route_disagreements(&extracted, &reported) {
+                        PrintCommand::Issue.print_agent_message(position, &disagreement);
+                    }
+                    fill_unknown_types(&mut extracted, &reported);
+                    extracted
+                }
+                (Ok(_), reported) => {
+                    PrintCommand::Issue.print_agent_message(
+                        position,
+                        "No routes found in the code, using the endpoints read by the model",
+                    );
+                    reported?
+                }
+                (Err(parse_error), reported) => {
+                    PrintCommand::Issue.print_agent_message(
+                        position,
+                        &format!(
+                        "The code could not be parsed ({}), using the endpoints read by the model",
+                        parse_error
+                    ),
+                    );
+                    reported?
+                }
+            };
+
+        // The route decides whether it is dynamic, not the flag the model printed
+        for endpoint in api_endpoints.iter_mut() {
+            endpoint.is_route_dynamic = !endpoint.path_parameters().is_empty();
+        }
+
+        // Save the normalized schema so later commands read the same types
+        let api_endpoints_json: String = serde_json::to_string_pretty(&api_endpoints)
+            .expect("RouteObject is always serializable to JSON");
+        save_api_endpoints(&self.config, &api_endpoints_json)?;
+
+        // Export it as OpenAPI for client generators and Swagger UI, only when it is valid
+        let openapi: Value = openapi_document(&api_endpoints, &self.config.server_url(""));
+        validate_openapi(&openapi).map_err(|errors| WizError::InvalidOpenApi { errors })?;
+        let openapi_json: String =
+            serde_json::to_string_pretty(&openapi).expect("Value is always serializable to JSON");
+        save_openapi_document(&self.config, &openapi_json)?;
+        Ok(api_endpoints)
+    }
+
+    // Runs the generated server and calls every endpoint with synthesized requests
+    // Reports every request which failed or answered unexpectedly, with the server stderr
+    pub async fn test_endpoints(
+        &self,
+        sandbox: &Sandbox,
+        endpoints: &[RouteObject],
+    ) -> Result<EndpointTestReport, WizError> {
+        // Run backend application
+        PrintCommand::UnitTest.print_agent_message(
+            self.attributes.position.as_str(),
+            "Backend Code Unit Testing: Starting web server...",
+        );
+
+        // Run the built binary in its own process group, it is killed with the handle
+        let mut server: ServerProcess = ServerProcess::start(&self.config, sandbox)?;
+
+        // Wait until the server accepts connections
+        PrintCommand::UnitTest.print_agent_message(
+            self.attributes.position.as_str(),
+            "Backend Code Unit Testing: Waiting for the server to accept connections...",
+        );
+
+        let startup_timeout: Duration =
+            Duration::from_secs(self.config.server.startup_timeout_secs);
+        match wait_for_port(
+            server.process_mut(),
+            self.config.server.port,
+            startup_timeout,
+        )
+        .await?
+        {
+            Readiness::Ready => {}
+            Readiness::Exited { code } => {
+                let exit_code: String =
+                    code.map_or("a signal".to_string(), |code: i32| code.to_string());
+                return Ok(EndpointTestReport {
+                    failures: vec![format!(
+                        "the server exited with {} before accepting connections on port {}",
+                        exit_code, self.config.server.port
+                    )],
+                    server_stderr: server.stop(),
+                });
+            }
+            Readiness::TimedOut => {
+                return Ok(EndpointTestReport {
+                    failures: vec![format!(
+                        "the server did not accept connections on port {} within {} seconds",
+                        self.config.server.port, self.config.server.startup_timeout_secs
+                    )],
+                    server_stderr: server.stop(),
+                });
+            }
+        }
+
+        // Create client with timout
+        let client: Client = http_client(Duration::from_secs(5))?;
+
+        // Run create, read, update and delete sequences per resource
+        let base_url: String = self.config.server_url("");
+        let mut synthesizer: RequestSynthesizer = RequestSynthesizer::new();
+        let mut failed_routes: Vec<String> = vec![];
+        for endpoint in RequestSynthesizer::crud_order(endpoints) {
+            // Confirm url testing
+            let testing_msg: String = format!(
+                "Testing endpoint '{} {}'...",
+                endpoint.method, endpoint.route
+            );
+            PrintCommand::UnitTest
+                .print_agent_message(self.attributes.position.as_str(), testing_msg.as_str());
+
+            if let Err(err_msg) = synthesizer
+                .check_endpoint(&client, &base_url, &endpoint)
+                .await
+            {
+                PrintCommand::Issue
+                    .print_agent_message(self.attributes.position.as_str(), err_msg.as_str());
+                failed_routes.push(err_msg);
+            }
+        }
+
+        PrintCommand::UnitTest.print_agent_message(
+            self.attributes.position.as_str(),
+            "Backend testing complete...",
+        );
+
+        Ok(EndpointTestReport {
+            failures: failed_routes,
+            server_stderr: server.stop(),
+        })
+    }
+
+    // Sends failing endpoints back to the fix step until the attempt budget is used up
+    fn record_runtime_failures(&mut self, report: &EndpointTestReport) -> Result<(), WizError> {
+        self.runtime_fix_count += 1;
+        if self.runtime_fix_count > self.config.server.runtime_fix_attempts {
+            PrintCommand::Issue.print_agent_message(
+                self.attributes.position.as_str(),
+                "Backend Code Unit Testing: Endpoints still failing, no fix attempts left",
+            );
+            return Err(WizError::EndpointTests {
+                failed_routes: report.failures.clone(),
+            });
+        }
+
+        self.bug_errors = Some(report.bug_errors());
+        Ok(())
     }
 }
 
@@ -123,23 +459,41 @@ impl SpecialFunctions for AgentBackendDeveloper {
         &self.attributes
     }
 
-    async fn execute(
-        &mut self,
-        factsheet: &mut FactSheet,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_agent_state(&mut self, state: AgentState) {
+        self.attributes.state = state;
+    }
+
+    fn requires(&self) -> Vec<FactSheetField> {
+        let mut fields: Vec<FactSheetField> = vec![
+            FactSheetField::ProjectDescription,
+            FactSheetField::ProjectScope,
+            FactSheetField::ExternalUrls,
+        ];
+        fields.extend(self.layers.iter().map(|layer: &BackendLayer| layer.field()));
+        fields
+    }
+
+    fn produces(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::BackendCode,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
+    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
         while self.attributes.state != AgentState::Finished {
             match &self.attributes.state {
                 AgentState::Discovery => {
-                    self.call_initial_backend_code(factsheet).await;
+                    self.call_initial_backend_code(factsheet).await?;
                     self.attributes.state = AgentState::Working;
                     continue;
                 }
 
                 AgentState::Working => {
-                    if self.bug_count == 0 {
-                        self.call_improved_backend_code(factsheet).await;
+                    if self.bug_errors.is_none() {
+                        self.call_improved_backend_code(factsheet).await?;
                     } else {
-                        self.call_fix_code_bugs(factsheet).await;
+                        self.call_fix_code_bugs(factsheet).await?;
                     }
                     self.attributes.state = AgentState::UnitTesting;
                     continue;
@@ -147,16 +501,7 @@ impl SpecialFunctions for AgentBackendDeveloper {
 
                 AgentState::UnitTesting => {
                     // Guard:: ENSURE AI SAFETY
-                    PrintCommand::UnitTest.print_agent_message(
-                        self.attributes.position.as_str(),
-                        "Backend Code Unit Testing: Requesting user input",
-                    );
-
-                    let is_safe_code: bool = confirm_safe_code();
-
-                    if !is_safe_code {
-                        panic!("Better go work on some AI alignment instead...")
-                    }
+                    self.confirm_run_generated_code()?;
 
                     // Build and Test Code
                     PrintCommand::UnitTest.print_agent_message(
@@ -164,26 +509,12 @@ impl SpecialFunctions for AgentBackendDeveloper {
                         "Backend Code Unit Testing: building project...",
                     );
 
-                    // Build Code
-                    let build_backend_server: std::process::Output = Command::new("cargo")
-                        .arg("build")
-                        .current_dir(WEB_SERVER_PROJECT_PATH)
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .output()
-                        .expect("Failed to build backend application");
+                    // Build Code in a disposable copy of the project
+                    let sandbox: Sandbox = self.create_sandbox()?;
+                    let build_res: Result<(), String> = self.build_server(&sandbox)?;
 
                     // Determine if build errors
-                    if build_backend_server.status.success() {
-                        self.bug_count = 0;
-                        PrintCommand::UnitTest.print_agent_message(
-                            self.attributes.position.as_str(),
-                            "Backend Code Unit Testing: Test server build successful...",
-                        );
-                    } else {
-                        let error_arr: Vec<u8> = build_backend_server.stderr;
-                        let error_str: String = String::from_utf8(error_arr).unwrap();
-
+                    if let Err(error_str) = build_res {
                         // Update error stats
                         self.bug_count += 1;
                         self.bug_errors = Some(error_str);
@@ -194,7 +525,10 @@ impl SpecialFunctions for AgentBackendDeveloper {
                                 self.attributes.position.as_str(),
                                 "Backend Code Unit Testing: Too many bugs found in code",
                             );
-                            panic!("Error: Too many bugs")
+                            return Err(WizError::BuildFailure {
+                                attempts: self.bug_count,
+                                errors: self.bug_errors.clone().unwrap_or_default(),
+                            });
                         }
 
                         // Pass back for rework
@@ -202,111 +536,26 @@ impl SpecialFunctions for AgentBackendDeveloper {
                         continue;
                     }
 
-                    /*
-                      Extract and Test
-                      Rest API Endpoints
-                    */
-
-                    // Extract API Endpoints
-                    let api_endpoints_str: String = self.call_extract_rest_api_endpoints().await;
-
-                    // Convert API Endpoints into Values
-                    let api_endpoints: Vec<RouteObject> =
-                        serde_json::from_str(api_endpoints_str.as_str())
-                            .expect("Failed to decode API Endpoints");
-
-                    // Define endpoints to check
-                    let check_endpoints: Vec<RouteObject> = api_endpoints
-                        .iter()
-                        .filter(|&route_object| {
-                            route_object.method == "get" && route_object.is_route_dynamic == "false"
-                        })
-                        .cloned()
-                        .collect();
-
-                    // Store API Endpoints
-                    factsheet.api_endpoint_schema = Some(check_endpoints.clone());
-
-                    // Run backend application
+                    self.bug_count = 0;
+                    self.bug_errors = None;
                     PrintCommand::UnitTest.print_agent_message(
                         self.attributes.position.as_str(),
-                        "Backend Code Unit Testing: Starting web server...",
+                        "Backend Code Unit Testing: Test server build successful...",
                     );
 
-                    // Execute running server
-                    let mut run_backend_server: std::process::Child = Command::new("cargo")
-                        .arg("run")
-                        .current_dir(WEB_SERVER_PROJECT_PATH)
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .spawn()
-                        .expect("Failed to run backend application");
+                    // Extract, store and test the API endpoints
+                    let api_endpoints: Vec<RouteObject> = self.extract_api_schema().await?;
+                    let report: EndpointTestReport =
+                        self.test_endpoints(&sandbox, &api_endpoints).await?;
+                    factsheet.api_endpoint_schema = Some(api_endpoints);
 
-                    // Let user know testing on server will take place soon
-                    PrintCommand::UnitTest.print_agent_message(
-                        self.attributes.position.as_str(),
-                        "Backend Code Unit Testing: Launching tests on server in 5 seconds...",
-                    );
-
-                    let seconds_sleep: Duration = Duration::from_secs(5);
-                    time::sleep(seconds_sleep).await;
-
-                    // Check status code
-                    for endpoint in check_endpoints {
-                        // Confirm url testing
-                        let testing_msg: String =
-                            format!("Testing endpoint '{}'...", endpoint.route);
-                        PrintCommand::UnitTest.print_agent_message(
-                            self.attributes.position.as_str(),
-                            testing_msg.as_str(),
-                        );
-
-                        // Create client with timout
-                        let client: Client = Client::builder()
-                            .timeout(Duration::from_secs(5))
-                            .build()
-                            .unwrap();
-
-                        // Test url
-                        let url: String = format!("http://localhost:8080{}", endpoint.route);
-                        match check_status_code(&client, &url).await {
-                            Ok(status_code) => {
-                                if status_code != 200 {
-                                    let err_msg: String = format!(
-                                        "WARNING: Failed to call backend url endpoint {}",
-                                        endpoint.route
-                                    );
-                                    PrintCommand::Issue.print_agent_message(
-                                        self.attributes.position.as_str(),
-                                        err_msg.as_str(),
-                                    );
-                                }
-                            }
-                            Err(e) => {
-                                // kill $(lsof -t -i:8080)
-                                run_backend_server
-                                    .kill()
-                                    .expect("Failed to kill backend web server");
-                                let err_msg: String = format!("Error checking backend {}", e);
-                                PrintCommand::Issue.print_agent_message(
-                                    self.attributes.position.as_str(),
-                                    err_msg.as_str(),
-                                );
-                            }
-                        }
+                    // Pass runtime failures back for rework
+                    if !report.failures.is_empty() {
+                        self.record_runtime_failures(&report)?;
+                        self.attributes.state = AgentState::Working;
+                        continue;
                     }
 
-                    save_api_endpoints(&api_endpoints_str);
-
-                    PrintCommand::UnitTest.print_agent_message(
-                        self.attributes.position.as_str(),
-                        "Backend testing complete...",
-                    );
-
-                    run_backend_server
-                        .kill()
-                        .expect("Failed to kill backend web server on completion");
-
                     self.attributes.state = AgentState::Finished;
                 }
 
@@ -320,11 +569,12 @@ impl SpecialFunctions for AgentBackendDeveloper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::helpers::approval::ApprovalPolicy;
+    use crate::helpers::test_support::{temp_server_workspace, ApproveAll};
+    use std::fs;
 
-    #[tokio::test]
-    async fn tests_backend_developer() {
-        let mut agent: AgentBackendDeveloper = AgentBackendDeveloper::new();
-
+    fn test_factsheet() -> FactSheet {
         let factsheet_str: &str = r#"
           {
             "project_description": "build a website that returns the currenct time",
@@ -337,13 +587,81 @@ mod tests {
             "backend_code": null,
             "api_endpoint_schema": null
           }"#;
+        serde_json::from_str(factsheet_str).unwrap()
+    }
 
-        let mut factsheet: FactSheet = serde_json::from_str(factsheet_str).unwrap();
-        //dbg!(factsheet);
-        //agent.attributes.state = AgentState::Discovery;
+    #[tokio::test]
+    #[ignore = "builds and runs the generated server, its dependencies are fetched from crates.io"]
+    async fn tests_backend_developer() {
+        let (output_dir, config) = temp_server_workspace("backend_developer");
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+        let mut agent: AgentBackendDeveloper =
+            AgentBackendDeveloper::new(Arc::new(provider), Arc::new(config))
+                .with_approval(Arc::new(ApproveAll));
+
+        let mut factsheet: FactSheet = test_factsheet();
         agent
             .execute(&mut factsheet)
             .await
             .expect("Failed to execute Backend Developer agent");
+        assert!(factsheet.api_endpoint_schema.is_some());
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_backend_developer_stops_when_declined() {
+        let (output_dir, config) = temp_server_workspace("backend_declined");
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+        let mut agent: AgentBackendDeveloper =
+            AgentBackendDeveloper::new(Arc::new(provider), Arc::new(config.clone()))
+                .with_approval(ApprovalPolicy::Deny.hook());
+
+        let mut factsheet: FactSheet = test_factsheet();
+        let err: WizError = agent.execute(&mut factsheet).await.unwrap_err();
+
+        assert!(err.is_user_abort());
+        assert_eq!(agent.attributes.state, AgentState::UnitTesting);
+        // The code is written to the workspace, but nothing was built
+        let backend_code: &str = factsheet.backend_code.as_deref().unwrap();
+        assert_eq!(
+            fs::read_to_string(config.exec_main_path()).unwrap(),
+            backend_code
+        );
+        assert!(!config.sandbox_target_path().exists());
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn tests_runtime_failures_within_budget() {
+        let mut config: WizConfig = WizConfig::resolve(None).expect("Failed to load wizai.toml");
+        config.server.runtime_fix_attempts = 1;
+        let mut agent: AgentBackendDeveloper =
+            AgentBackendDeveloper::new(Arc::new(MockProvider::new()), Arc::new(config));
+
+        let server_stderr: String = (0..50)
+            .map(|line: usize| format!("log line {}\n", line))
+            .collect::<String>()
+            + "thread 'actix-rt' panicked at src/main.rs:42:10";
+        let report: EndpointTestReport = EndpointTestReport {
+            failures: vec!["GET /task/1 returned status 500: ".to_string()],
+            server_stderr,
+        };
+
+        agent.record_runtime_failures(&report).unwrap();
+        let bug_errors: String = agent.bug_errors.clone().unwrap();
+        assert!(bug_errors.contains("GET /task/1 returned status 500"));
+        assert!(bug_errors.ends_with("panicked at src/main.rs:42:10"));
+        assert!(!bug_errors.contains("log line 10\n"));
+
+        match agent.record_runtime_failures(&report) {
+            Err(WizError::EndpointTests { failed_routes }) => {
+                assert_eq!(failed_routes, report.failures)
+            }
+            other => panic!("Expected the fix budget to run out, got {:?}", other),
+        }
     }
 }
@@ -0,0 +1,617 @@
+use crate::ai_functions::aifunc_backend::{
+    print_blockchain_integration_code, print_fixed_code, print_json_rpc_code,
+    print_wallet_integration_code,
+};
+use crate::apis::dev_chain::DevChain;
+use crate::apis::llm_provider::LlmProvider;
+use crate::helpers::approval::{ApprovalHook, ApprovalRequest, TerminalApproval};
+use crate::helpers::code_fence::{extract_code, RUST_TAGS};
+use crate::helpers::command_line::PrintCommand;
+use crate::helpers::config::WizConfig;
+use crate::helpers::general::{
+    ai_task_request, declare_module, read_exec_main_contents, save_backend_code,
+    save_blockchain_module, save_contract_abi,
+};
+use crate::helpers::sandbox::{Sandbox, SandboxStep};
+use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
+use crate::models::agents::agent_contract::AgentSmartContractDeveloper;
+use crate::models::agents::agent_traits::{
+    BlockchainModule, CheckOutcome, ContractCheck, FactSheet, FactSheetField, SmartContract,
+    SpecialFunctions,
+};
+use crate::models::general::error::WizError;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+
+// Modules generated into the blockchain directory of the web server
+const CONTRACTS_MODULE: &str = "contracts";
+const WALLET_MODULE: &str = "wallet";
+const JSON_RPC_MODULE: &str = "json_rpc";
+
+// Shared by every module prompt so the modules fit together
+const MODULE_REQUIREMENTS: &str = "The module is part of the actix-web server crate and uses ethers = \"1.0\" (use ethers::prelude::*). \
+Read the node url from the ETH_RPC_URL environment variable (default http://127.0.0.1:8545) and the signing key from PRIVATE_KEY \
+(default: the first anvil dev key 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4ff80). \
+Never hard code a public network. Contract bindings are generated with abigen! from the ABI_PATH of each contract (relative to the crate root). \
+Sibling modules are reachable as super::contracts, super::wallet and super::json_rpc. \
+Add a #[tokio::test] smoke test which talks to the node at ETH_RPC_URL.";
+
+// Blockchain Integration Developer
+// Wires the actix server to the deployed contracts with ethers-rs modules
+#[derive(Debug)]
+pub struct AgentBlockchainIntegrator {
+    attributes: BasicAgent,
+    bug_errors: Option<String>,
+    bug_count: u8,
+    provider: Arc<dyn LlmProvider>,
+    config: Arc<WizConfig>,
+    approval: Arc<dyn ApprovalHook>,
+}
+
+impl AgentBlockchainIntegrator {
+    pub const POSITION: &'static str = "Blockchain Integration Developer";
+
+    pub fn new(provider: Arc<dyn LlmProvider>, config: Arc<WizConfig>) -> Self {
+        let attributes: BasicAgent = BasicAgent {
+            objective: "Connects the web server to the deployed smart contracts over JSON-RPC"
+                .to_string(),
+            position: Self::POSITION.to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes,
+            bug_errors: None,
+            bug_count: 0,
+            provider,
+            config,
+            approval: Arc::new(TerminalApproval),
+        }
+    }
+
+    // Decides whether the generated code may be built and tested
+    pub fn with_approval(mut self, approval: Arc<dyn ApprovalHook>) -> Self {
+        self.approval = approval;
+        self
+    }
+
+    // Contracts which were deployed while testing them and can be integrated
+    fn deployed_contracts(factsheet: &FactSheet) -> Vec<&SmartContract> {
+        factsheet
+            .smart_contracts
+            .iter()
+            .flatten()
+            .filter(|contract: &&SmartContract| contract.address.is_some())
+            .collect()
+    }
+
+    // Writes the ABIs for abigen! and declares the blockchain module in main.rs
+    // The factsheet keeps the main.rs on disk, so a later run does not write back the old one
+    fn prepare_server_project(&self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        for contract in Self::deployed_contracts(factsheet) {
+            save_contract_abi(&self.config, &contract.name, &contract.abi)?;
+        }
+
+        let module_list: String = [CONTRACTS_MODULE, JSON_RPC_MODULE, WALLET_MODULE]
+            .iter()
+            .map(|module: &&str| format!("pub mod {};\n", module))
+            .collect();
+        save_blockchain_module(&self.config, "mod", &module_list)?;
+
+        let mut main_code: String = read_exec_main_contents(&self.config)?;
+        if let Some(declared_code) = Self::declare_blockchain_module(&self.config, &main_code) {
+            save_backend_code(&self.config, &declared_code)?;
+            main_code = declared_code;
+        }
+        factsheet.backend_code = Some(main_code);
+        Ok(())
+    }
+
+    // main.rs with the blockchain module declared, None if it already is
+    pub fn declare_blockchain_module(config: &WizConfig, main_code: &str) -> Option<String> {
+        let module_name: String = config
+            .blockchain_path()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "blockchain".to_string());
+        declare_module(main_code, &module_name)
+    }
+
+    // Name, address and ABI location of every deployed contract
+    fn contracts_context(&self, factsheet: &FactSheet) -> String {
+        let server_path: PathBuf = self.config.web_server_project_path();
+        let contracts: Vec<Value> = Self::deployed_contracts(factsheet)
+            .iter()
+            .map(|contract: &&SmartContract| {
+                let abi_path: PathBuf = self
+                    .config
+                    .abi_path()
+                    .join(format!("{}.json", contract.name));
+                let abi_path: &Path = abi_path.strip_prefix(&server_path).unwrap_or(&abi_path);
+                json!({
+                    "name": contract.name,
+                    "address": contract.address,
+                    "abi_path": abi_path,
+                    "abi": contract.abi,
+                })
+            })
+            .collect();
+        serde_json::to_string(&contracts).expect("Contracts are always serializable to JSON")
+    }
+
+    async fn call_module_code(
+        &self,
+        factsheet: &FactSheet,
+        module_name: &str,
+        function_name: &str,
+        function_pass: fn(&str) -> &'static str,
+    ) -> Result<BlockchainModule, WizError> {
+        let msg_context: String = format!(
+            "PROJECT_DESCRIPTION: {} \n CODE_TEMPLATE: the Rust module src/blockchain/{}.rs \n CONTRACTS: {} \n REQUIREMENTS: {} \n",
+            factsheet.project_description,
+            module_name,
+            self.contracts_context(factsheet),
+            MODULE_REQUIREMENTS
+        );
+
+        let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            function_name,
+            function_pass,
+        )
+        .await?;
+
+        self.store_module(module_name, &ai_response)
+    }
+
+    async fn call_integration_modules(&self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        let modules: Vec<BlockchainModule> = vec![
+            self.call_module_code(
+                factsheet,
+                CONTRACTS_MODULE,
+                get_function_string!(print_blockchain_integration_code),
+                print_blockchain_integration_code,
+            )
+            .await?,
+            self.call_module_code(
+                factsheet,
+                WALLET_MODULE,
+                get_function_string!(print_wallet_integration_code),
+                print_wallet_integration_code,
+            )
+            .await?,
+            self.call_module_code(
+                factsheet,
+                JSON_RPC_MODULE,
+                get_function_string!(print_json_rpc_code),
+                print_json_rpc_code,
+            )
+            .await?,
+        ];
+
+        factsheet.blockchain_modules = Some(modules);
+        Ok(())
+    }
+
+    async fn call_fix_module_bugs(&self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        let error_str: String = self.bug_errors.clone().unwrap_or_default();
+        let mut modules: Vec<BlockchainModule> =
+            factsheet.blockchain_modules.clone().unwrap_or_default();
+        let broken_modules: Vec<String> = Self::modules_to_fix(&modules, &error_str);
+
+        for module in modules.iter_mut() {
+            if !broken_modules.contains(&module.name) {
+                continue;
+            }
+
+            let msg_context: String = format!(
+                "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n
+      THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+                module.code, error_str
+            );
+
+            let ai_response: String = ai_task_request(
+                self.provider.as_ref(),
+                msg_context,
+                &self.attributes.position,
+                get_function_string!(print_fixed_code),
+                print_fixed_code,
+            )
+            .await?;
+
+            *module = self.store_module(&module.name, &ai_response)?;
+        }
+
+        factsheet.blockchain_modules = Some(modules);
+        Ok(())
+    }
+
+    fn store_module(
+        &self,
+        module_name: &str,
+        ai_response: &str,
+    ) -> Result<BlockchainModule, WizError> {
+        let code: String = extract_code(ai_response, &RUST_TAGS);
+        save_blockchain_module(&self.config, module_name, &code)?;
+        Ok(BlockchainModule {
+            name: module_name.to_string(),
+            code,
+        })
+    }
+
+    // Modules named in the compiler output, every module when none is named
+    pub fn modules_to_fix(modules: &[BlockchainModule], errors: &str) -> Vec<String> {
+        let named: Vec<String> = modules
+            .iter()
+            .filter(|module: &&BlockchainModule| {
+                let file_name: String = format!("{}.rs", module.name);
+                errors.lines().any(|line: &str| {
+                    line.contains("-->") && line.contains(&format!("blockchain/{}", file_name))
+                })
+            })
+            .map(|module: &BlockchainModule| module.name.clone())
+            .collect();
+
+        if named.is_empty() {
+            modules
+                .iter()
+                .map(|module: &BlockchainModule| module.name.clone())
+                .collect()
+        } else {
+            named
+        }
+    }
+
+    // Guard:: ENSURE AI SAFETY
+    fn confirm_run_generated_code(&self) -> Result<(), WizError> {
+        let request: ApprovalRequest = ApprovalRequest {
+            position: self.attributes.position.clone(),
+            action: "build and test the generated blockchain integration".to_string(),
+            project_path: self.config.web_server_project_path(),
+        };
+
+        if !self.approval.approve(&request) {
+            return Err(WizError::UserAbort {
+                reason: self.approval.decline_reason(),
+            });
+        }
+        Ok(())
+    }
+
+    // Copy of the web server project with the modules, in which they are built and tested
+    fn create_sandbox(&self) -> Result<Sandbox, WizError> {
+        let sandbox: Sandbox = Sandbox::create(&self.config)?;
+        let sandbox_msg: String = format!(
+            "Blockchain Integration Testing: Sandbox in {} ({:?})",
+            sandbox.project_path().display(),
+            sandbox.isolation()
+        );
+        PrintCommand::UnitTest
+            .print_agent_message(self.attributes.position.as_str(), sandbox_msg.as_str());
+
+        sandbox.fetch_dependencies();
+        Ok(sandbox)
+    }
+
+    // Runs cargo in the sandbox, returns the error output on failure
+    fn run_cargo(
+        &self,
+        sandbox: &Sandbox,
+        step: SandboxStep,
+        args: &[&str],
+        envs: &[(&str, String)],
+    ) -> Result<Option<String>, WizError> {
+        let command: String = format!("cargo {}", args.join(" "));
+        let output: std::process::Output = sandbox
+            .command("cargo", step)
+            .args(args)
+            .envs(envs.iter().map(|(key, value)| (key, value)))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| WizError::process(&command, e))?;
+
+        if output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stderr),
+            String::from_utf8_lossy(&output.stdout)
+        )))
+    }
+
+    // Redeploys the contracts to a fresh dev chain and runs the smoke tests of the modules against it
+    // The tests are compiled without network and run with it, to reach the dev chain
+    // Returns the failed test output, if any
+    async fn smoke_test(
+        &self,
+        sandbox: &Sandbox,
+        factsheet: &FactSheet,
+    ) -> Result<Option<String>, WizError> {
+        if let Some(build_errors) =
+            self.run_cargo(sandbox, SandboxStep::Build, &["test", "--no-run"], &[])?
+        {
+            return Ok(Some(build_errors));
+        }
+
+        PrintCommand::UnitTest.print_agent_message(
+            self.attributes.position.as_str(),
+            "Blockchain Integration Testing: smoke testing against a local JSON-RPC node...",
+        );
+
+        let dev_chain: DevChain = DevChain::start(&self.config).await?;
+        let accounts: Vec<String> = dev_chain.accounts().await?;
+        let sender: &String = accounts
+            .first()
+            .ok_or_else(|| WizError::dev_chain("no funded dev account"))?;
+
+        // A fresh chain deploying in the same order reproduces the addresses the modules use
+        let mut contracts: Vec<SmartContract> =
+            factsheet.smart_contracts.clone().unwrap_or_default();
+        let expected: Vec<Option<String>> = contracts
+            .iter()
+            .map(|contract: &SmartContract| contract.address.clone())
+            .collect();
+        let deploys: Vec<ContractCheck> =
+            AgentSmartContractDeveloper::deploy_contracts(&dev_chain, sender, &mut contracts)
+                .await?;
+        if let Some(failed) = deploys
+            .iter()
+            .find(|check: &&ContractCheck| check.outcome == CheckOutcome::Failed)
+        {
+            return Err(WizError::dev_chain(format!(
+                "redeploying {} failed: {}",
+                failed.contract, failed.detail
+            )));
+        }
+        for (contract, expected) in contracts.iter().zip(expected) {
+            if expected.is_some() && contract.address != expected {
+                return Err(WizError::dev_chain(format!(
+                    "{} was redeployed at {:?} instead of {:?}",
+                    contract.name, contract.address, expected
+                )));
+            }
+        }
+
+        self.run_cargo(
+            sandbox,
+            SandboxStep::Run,
+            &["test"],
+            &[("ETH_RPC_URL", self.config.dev_chain_url())],
+        )
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for AgentBlockchainIntegrator {
+    fn get_attributes_from_agent(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn set_agent_state(&mut self, state: AgentState) {
+        self.attributes.state = state;
+    }
+
+    fn requires(&self) -> Vec<FactSheetField> {
+        // The modules are declared in the main.rs written by the Backend Developer
+        vec![FactSheetField::BackendCode, FactSheetField::SmartContracts]
+    }
+
+    fn produces(&self) -> Vec<FactSheetField> {
+        // main.rs gains the declaration of the blockchain module
+        vec![
+            FactSheetField::BackendCode,
+            FactSheetField::BlockchainModules,
+        ]
+    }
+
+    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        if Self::deployed_contracts(factsheet).is_empty() {
+            PrintCommand::AICall.print_agent_message(
+                self.attributes.position.as_str(),
+                "No deployed contracts to integrate",
+            );
+            self.attributes.state = AgentState::Finished;
+        }
+
+        while self.attributes.state != AgentState::Finished {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    self.prepare_server_project(factsheet)?;
+                    self.attributes.state = AgentState::Working;
+                    continue;
+                }
+
+                AgentState::Working => {
+                    if self.bug_count == 0 {
+                        self.call_integration_modules(factsheet).await?;
+                    } else {
+                        self.call_fix_module_bugs(factsheet).await?;
+                    }
+                    self.attributes.state = AgentState::UnitTesting;
+                    continue;
+                }
+
+                AgentState::UnitTesting => {
+                    // Guard:: ENSURE AI SAFETY
+                    self.confirm_run_generated_code()?;
+
+                    PrintCommand::UnitTest.print_agent_message(
+                        self.attributes.position.as_str(),
+                        "Blockchain Integration Testing: building project...",
+                    );
+
+                    // Generated code only runs in a disposable copy of the project
+                    let sandbox: Sandbox = self.create_sandbox()?;
+                    let errors: Option<String> =
+                        match self.run_cargo(&sandbox, SandboxStep::Build, &["build"], &[])? {
+                            Some(build_errors) => Some(build_errors),
+                            None => self.smoke_test(&sandbox, factsheet).await?,
+                        };
+
+                    match errors {
+                        None => {
+                            self.bug_count = 0;
+                            PrintCommand::UnitTest.print_agent_message(
+                                self.attributes.position.as_str(),
+                                "Blockchain Integration Testing: build and smoke test successful...",
+                            );
+                            self.attributes.state = AgentState::Finished;
+                        }
+                        Some(error_str) => {
+                            // Update error stats
+                            self.bug_count += 1;
+                            self.bug_errors = Some(error_str);
+
+                            // Exit if too many bugs
+                            if self.bug_count > 2 {
+                                PrintCommand::Issue.print_agent_message(
+                                    self.attributes.position.as_str(),
+                                    "Blockchain Integration Testing: Too many bugs found in code",
+                                );
+                                return Err(WizError::BuildFailure {
+                                    attempts: self.bug_count,
+                                    errors: self.bug_errors.clone().unwrap_or_default(),
+                                });
+                            }
+
+                            // Pass back for rework
+                            self.attributes.state = AgentState::Working;
+                        }
+                    }
+                }
+
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::helpers::approval::ApprovalPolicy;
+    use crate::helpers::test_support::temp_workspace_config;
+    use crate::models::agents::agent_traits::ProjectScope;
+    use std::fs;
+
+    fn test_config(name: &str) -> (PathBuf, Arc<WizConfig>) {
+        let (output_dir, config) = temp_workspace_config(&format!("blockchain_{}", name));
+        fs::create_dir_all(config.exec_main_path().parent().unwrap()).unwrap();
+        fs::write(config.exec_main_path(), "fn main() {}\n").unwrap();
+        (output_dir, Arc::new(config))
+    }
+
+    fn test_factsheet(address: Option<&str>) -> FactSheet {
+        FactSheet {
+            project_description: "build a website where users can mint and send reward tokens"
+                .to_string(),
+            project_scope: Some(ProjectScope {
+                is_crud_required: false,
+                is_user_login_and_logout: false,
+                is_external_urls_required: false,
+                is_blockchain_required: true,
+            }),
+            smart_contracts: Some(vec![SmartContract {
+                name: "MyToken".to_string(),
+                source_path: PathBuf::from("server-template/contracts/MyToken.sol"),
+                abi: json!([{"type": "function", "name": "totalSupply", "stateMutability": "view", "inputs": []}]),
+                bytecode: Some("6080604052".to_string()),
+                address: address.map(|address: &str| address.to_string()),
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_integrator_writes_modules_before_approval() {
+        let provider: Arc<MockProvider> =
+            Arc::new(MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures"));
+        let (output_dir, config) = test_config("declined");
+        let mut agent: AgentBlockchainIntegrator =
+            AgentBlockchainIntegrator::new(provider.clone(), config.clone())
+                .with_approval(ApprovalPolicy::Deny.hook());
+
+        let mut factsheet: FactSheet =
+            test_factsheet(Some("0x5fbdb2315678afecb367f032d93f642f64180aa3"));
+        let err: WizError = agent
+            .execute(&mut factsheet)
+            .await
+            .expect_err("Running generated code is denied");
+        assert!(err.is_user_abort());
+        assert_eq!(
+            agent.get_attributes_from_agent().state,
+            AgentState::UnitTesting
+        );
+
+        assert_eq!(
+            provider.calls(),
+            vec![
+                "print_blockchain_integration_code",
+                "print_wallet_integration_code",
+                "print_json_rpc_code"
+            ]
+        );
+        let modules: Vec<BlockchainModule> = factsheet.blockchain_modules.unwrap();
+        assert_eq!(modules.len(), 3);
+        for module in &modules {
+            let path: PathBuf = config.blockchain_path().join(format!("{}.rs", module.name));
+            assert_eq!(fs::read_to_string(path).unwrap(), module.code);
+        }
+
+        let main_code: String = fs::read_to_string(config.exec_main_path()).unwrap();
+        assert!(main_code.starts_with("#[allow(dead_code)]\nmod blockchain;\n"));
+        assert_eq!(factsheet.backend_code, Some(main_code));
+        assert!(config.abi_path().join("MyToken.json").is_file());
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_integrator_skips_without_deployed_contracts() {
+        let (output_dir, config) = test_config("skips");
+        let mut agent: AgentBlockchainIntegrator =
+            AgentBlockchainIntegrator::new(Arc::new(MockProvider::new()), config);
+
+        let mut factsheet: FactSheet = test_factsheet(None);
+        agent
+            .execute(&mut factsheet)
+            .await
+            .expect("Nothing to integrate");
+        assert!(factsheet.blockchain_modules.is_none());
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn tests_modules_to_fix() {
+        let modules: Vec<BlockchainModule> = [CONTRACTS_MODULE, WALLET_MODULE, JSON_RPC_MODULE]
+            .iter()
+            .map(|name: &&str| BlockchainModule {
+                name: name.to_string(),
+                code: String::new(),
+            })
+            .collect();
+
+        let errors: &str = "error[E0425]: cannot find value `provider` in this scope\n  --> src/blockchain/wallet.rs:12:5";
+        assert_eq!(
+            AgentBlockchainIntegrator::modules_to_fix(&modules, errors),
+            vec!["wallet"]
+        );
+
+        let errors: &str = "error: linking with `cc` failed";
+        assert_eq!(
+            AgentBlockchainIntegrator::modules_to_fix(&modules, errors).len(),
+            3
+        );
+    }
+}
@@ -0,0 +1,843 @@
+use crate::ai_functions::aifunc_backend::{
+    print_fixed_contract_code, print_smart_contract_code, print_token_transaction_code,
+};
+use crate::apis::dev_chain::{
+    decode_uint, encode_address, encode_uint, function_selector, DevChain,
+};
+use crate::apis::llm_provider::LlmProvider;
+use crate::helpers::code_fence::{extract_code, SOLIDITY_TAGS};
+use crate::helpers::command_line::PrintCommand;
+use crate::helpers::config::WizConfig;
+use crate::helpers::general::{
+    ai_task_request, read_contract_template_contents, save_contract_code,
+};
+use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
+use crate::models::agents::agent_traits::{
+    CheckOutcome, ContractCheck, FactSheet, FactSheetField, SmartContract, SpecialFunctions,
+};
+use crate::models::general::error::WizError;
+
+use async_trait::async_trait;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+// Smart Contract Developer
+// Writes the Solidity contracts of the project and checks them with solc
+#[derive(Debug)]
+pub struct AgentSmartContractDeveloper {
+    attributes: BasicAgent,
+    bug_errors: Option<String>,
+    bug_count: u8,
+    provider: Arc<dyn LlmProvider>,
+    config: Arc<WizConfig>,
+}
+
+impl AgentSmartContractDeveloper {
+    pub const POSITION: &'static str = "Smart Contract Developer";
+
+    pub fn new(provider: Arc<dyn LlmProvider>, config: Arc<WizConfig>) -> Self {
+        let attributes: BasicAgent = BasicAgent {
+            objective: "Develops and compiles the Solidity smart contracts of the project"
+                .to_string(),
+            position: Self::POSITION.to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes,
+            bug_errors: None,
+            bug_count: 0,
+            provider,
+            config,
+        }
+    }
+
+    async fn call_initial_contract_code(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), WizError> {
+        let code_template_str: String = read_contract_template_contents(&self.config)?;
+
+        // Concatenate Instruction
+        let msg_context: String = format!(
+            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n",
+            code_template_str, factsheet.project_description
+        );
+
+        let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_smart_contract_code),
+            print_smart_contract_code,
+        )
+        .await?;
+
+        self.store_contract_code(factsheet, &ai_response)
+    }
+
+    async fn call_token_transaction_code(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), WizError> {
+        let msg_context: String = format!(
+            "CODE TEMPLATE: {:?} \n PROJECT_DESCRIPTION: {} \n LANGUAGE: the code template is a Solidity contract, print the complete Solidity contract \n",
+            factsheet.contract_code, factsheet.project_description
+        );
+
+        let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_token_transaction_code),
+            print_token_transaction_code,
+        )
+        .await?;
+
+        self.store_contract_code(factsheet, &ai_response)
+    }
+
+    async fn call_fix_contract_bugs(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        let msg_context: String = format!(
+            "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n
+      THIS FUNCTION ONLY OUTPUTS SOLIDITY CODE. JUST OUTPUT THE FIXED CONTRACT.",
+            factsheet.contract_code.clone().unwrap_or_default(),
+            self.bug_errors.clone().unwrap_or_default()
+        );
+
+        let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_fixed_contract_code),
+            print_fixed_contract_code,
+        )
+        .await?;
+
+        self.store_contract_code(factsheet, &ai_response)
+    }
+
+    fn store_contract_code(
+        &self,
+        factsheet: &mut FactSheet,
+        ai_response: &str,
+    ) -> Result<(), WizError> {
+        let contract_code: String = extract_code(ai_response, &SOLIDITY_TAGS);
+        factsheet.contract_code = Some(contract_code);
+        Ok(())
+    }
+
+    // Name of the last concrete contract declared in the source, the one inheriting the others
+    // Interfaces, libraries and abstract contracts cannot be deployed
+    pub fn contract_name(contract_code: &str) -> Option<String> {
+        contract_code.lines().rev().find_map(|line: &str| {
+            let name: String = line
+                .trim_start()
+                .strip_prefix("contract ")?
+                .trim_start()
+                .chars()
+                .take_while(|c: &char| c.is_alphanumeric() || *c == '_')
+                .collect();
+            if name.is_empty() {
+                None
+            } else {
+                Some(name)
+            }
+        })
+    }
+
+    // Writes the source and compiles it with solc
+    // Compiler errors are returned as Ok(Err(errors)) so they can be fed back for fixing
+    fn compile_contracts(
+        &self,
+        contract_code: &str,
+    ) -> Result<Result<Vec<SmartContract>, String>, WizError> {
+        let contract_name: String =
+            Self::contract_name(contract_code).unwrap_or_else(|| "Contract".to_string());
+        let source_path: PathBuf = save_contract_code(&self.config, &contract_name, contract_code)?;
+
+        // Start from an empty build directory so only artifacts of this source are picked up
+        let build_path: PathBuf = self.config.contracts_build_path();
+        match fs::remove_dir_all(&build_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(WizError::file_io(build_path.display(), e)),
+        }
+
+        let solc: &Path = &self.config.contracts.solc;
+        let compile_output: std::process::Output = Command::new(solc)
+            .args(["--abi", "--bin", "--overwrite", "-o"])
+            .arg(&build_path)
+            .arg(&source_path)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| WizError::process(&solc.display().to_string(), e))?;
+
+        if !compile_output.status.success() {
+            let error_str: String = String::from_utf8_lossy(&compile_output.stderr).to_string();
+            return Ok(Err(error_str));
+        }
+
+        Self::read_artifacts(&build_path, &source_path).map(Ok)
+    }
+
+    // Every <Name>.abi written by solc, with the matching <Name>.bin
+    fn read_artifacts(
+        build_path: &Path,
+        source_path: &Path,
+    ) -> Result<Vec<SmartContract>, WizError> {
+        let entries: fs::ReadDir =
+            fs::read_dir(build_path).map_err(|e| WizError::file_io(build_path.display(), e))?;
+
+        let mut contracts: Vec<SmartContract> = vec![];
+        for entry in entries {
+            let abi_path: PathBuf = entry
+                .map_err(|e| WizError::file_io(build_path.display(), e))?
+                .path();
+            if abi_path.extension().and_then(|ext| ext.to_str()) != Some("abi") {
+                continue;
+            }
+
+            let abi_str: String = fs::read_to_string(&abi_path)
+                .map_err(|e| WizError::file_io(abi_path.display(), e))?;
+            let abi: serde_json::Value =
+                serde_json::from_str(&abi_str).map_err(|e: serde_json::Error| {
+                    WizError::file_io(
+                        abi_path.display(),
+                        io::Error::new(io::ErrorKind::InvalidData, e),
+                    )
+                })?;
+
+            let bytecode: Option<String> = fs::read_to_string(abi_path.with_extension("bin"))
+                .ok()
+                .map(|bin: String| bin.trim().to_string())
+                .filter(|bin: &String| !bin.is_empty());
+
+            let name: String = abi_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            contracts.push(SmartContract {
+                name,
+                source_path: source_path.to_path_buf(),
+                abi,
+                bytecode,
+                address: None,
+            });
+        }
+
+        contracts.sort_by(|a: &SmartContract, b: &SmartContract| a.name.cmp(&b.name));
+        Ok(contracts)
+    }
+
+    // Deploys the compiled contracts to a fresh local dev chain and checks them
+    // Failed checks are returned as Ok(Err(failures)) so they can be fed back for fixing
+    async fn test_on_dev_chain(
+        &self,
+        mut contracts: Vec<SmartContract>,
+        factsheet: &mut FactSheet,
+    ) -> Result<Result<Vec<SmartContract>, String>, WizError> {
+        if contracts.iter().all(|contract| contract.bytecode.is_none()) {
+            return Ok(Ok(contracts));
+        }
+
+        let starting_msg: String = format!(
+            "Contract Unit Testing: deploying to local dev chain on {}...",
+            self.config.dev_chain_url()
+        );
+        PrintCommand::UnitTest
+            .print_agent_message(self.attributes.position.as_str(), starting_msg.as_str());
+
+        let dev_chain: DevChain = DevChain::start(&self.config).await?;
+        let checks: Vec<ContractCheck> =
+            Self::run_contract_checks(&dev_chain, &mut contracts).await?;
+        drop(dev_chain);
+
+        let failures: Vec<String> = checks
+            .iter()
+            .filter(|check: &&ContractCheck| check.outcome == CheckOutcome::Failed)
+            .map(|check: &ContractCheck| {
+                format!("{} {}: {}", check.contract, check.check, check.detail)
+            })
+            .collect();
+
+        let checked_msg: String = format!(
+            "Contract Unit Testing: {} checks run, {} failed...",
+            checks.len(),
+            failures.len()
+        );
+        PrintCommand::UnitTest
+            .print_agent_message(self.attributes.position.as_str(), checked_msg.as_str());
+        factsheet.contract_checks = Some(checks);
+
+        if failures.is_empty() {
+            Ok(Ok(contracts))
+        } else {
+            Ok(Err(failures.join("\n")))
+        }
+    }
+
+    // Deploys every contract with bytecode from `sender` and records its address
+    pub async fn deploy_contracts(
+        dev_chain: &DevChain,
+        sender: &str,
+        contracts: &mut [SmartContract],
+    ) -> Result<Vec<ContractCheck>, WizError> {
+        let mut checks: Vec<ContractCheck> = vec![];
+        for contract in contracts.iter_mut() {
+            contract.address = None;
+            let Some(bytecode) = contract.bytecode.clone() else {
+                continue;
+            };
+
+            let takes_constructor_args: bool =
+                contract
+                    .abi
+                    .as_array()
+                    .is_some_and(|abi: &Vec<serde_json::Value>| {
+                        abi.iter().any(|item| {
+                            item["type"] == "constructor"
+                                && item["inputs"]
+                                    .as_array()
+                                    .is_some_and(|inputs| !inputs.is_empty())
+                        })
+                    });
+            let (outcome, detail) = if takes_constructor_args {
+                (
+                    CheckOutcome::Skipped,
+                    "constructor takes arguments".to_string(),
+                )
+            } else {
+                match dev_chain.deploy(sender, &bytecode).await? {
+                    Ok(address) => {
+                        contract.address = Some(address.clone());
+                        (CheckOutcome::Passed, address)
+                    }
+                    Err(reason) => (CheckOutcome::Failed, reason),
+                }
+            };
+
+            checks.push(ContractCheck {
+                contract: contract.name.clone(),
+                check: "deploy".to_string(),
+                outcome,
+                detail,
+            });
+        }
+        Ok(checks)
+    }
+
+    // Deploys every contract with bytecode from the first dev account, then
+    // calls its argument-less view functions and tries a token transfer to the second account
+    pub async fn run_contract_checks(
+        dev_chain: &DevChain,
+        contracts: &mut [SmartContract],
+    ) -> Result<Vec<ContractCheck>, WizError> {
+        let accounts: Vec<String> = dev_chain.accounts().await?;
+        let (sender, recipient) = match accounts.as_slice() {
+            [sender, recipient, ..] => (sender.clone(), recipient.clone()),
+            _ => {
+                return Err(WizError::dev_chain(
+                    "at least two funded dev accounts are needed",
+                ))
+            }
+        };
+
+        // Everything is deployed before the checks send transactions, so the addresses
+        // only depend on the deploy order and a fresh chain reproduces them
+        let mut checks: Vec<ContractCheck> =
+            Self::deploy_contracts(dev_chain, &sender, contracts).await?;
+        for contract in contracts.iter() {
+            let Some(address) = contract.address.clone() else {
+                continue;
+            };
+            let mut record = |check: &str, outcome: CheckOutcome, detail: String| {
+                checks.push(ContractCheck {
+                    contract: contract.name.clone(),
+                    check: check.to_string(),
+                    outcome,
+                    detail,
+                })
+            };
+            let abi: Vec<serde_json::Value> = contract.abi.as_array().cloned().unwrap_or_default();
+
+            // Getters and other view functions without arguments must not revert
+            let functions: Vec<&serde_json::Value> = abi
+                .iter()
+                .filter(|item| item["type"] == "function")
+                .collect();
+            for function in &functions {
+                let is_view: bool = matches!(
+                    function["stateMutability"].as_str(),
+                    Some("view") | Some("pure")
+                );
+                let takes_args: bool = function["inputs"]
+                    .as_array()
+                    .is_some_and(|inputs| !inputs.is_empty());
+                if !is_view || takes_args {
+                    continue;
+                }
+
+                let signature: String = abi_signature(function);
+                let check: String = format!("call {}", signature);
+                match dev_chain
+                    .call(&address, &function_selector(&signature))
+                    .await?
+                {
+                    Ok(output) => record(&check, CheckOutcome::Passed, output),
+                    Err(reason) => record(&check, CheckOutcome::Failed, reason),
+                }
+            }
+
+            // Token transfer of one unit, when the contract looks like a token
+            let has_function = |signature: &str| {
+                functions
+                    .iter()
+                    .any(|function| abi_signature(function) == signature)
+            };
+            if !has_function("transfer(address,uint256)") {
+                continue;
+            }
+            let has_balance_of: bool = has_function("balanceOf(address)");
+            let balance_of = |account: &str| {
+                format!(
+                    "{}{}",
+                    function_selector("balanceOf(address)"),
+                    encode_address(account)
+                )
+            };
+
+            if has_balance_of {
+                let balance: Option<u128> = dev_chain
+                    .call(&address, &balance_of(&sender))
+                    .await?
+                    .ok()
+                    .and_then(|output| decode_uint(&output));
+                if balance == Some(0) {
+                    record(
+                        "transfer(address,uint256)",
+                        CheckOutcome::Skipped,
+                        "deployer holds no tokens".to_string(),
+                    );
+                    continue;
+                }
+            }
+
+            let transfer: String = format!(
+                "{}{}{}",
+                function_selector("transfer(address,uint256)"),
+                encode_address(&recipient),
+                encode_uint(1)
+            );
+            if let Err(reason) = dev_chain.transact(&sender, &address, &transfer).await? {
+                record("transfer(address,uint256)", CheckOutcome::Failed, reason);
+                continue;
+            }
+
+            let received: bool = if has_balance_of {
+                dev_chain
+                    .call(&address, &balance_of(&recipient))
+                    .await?
+                    .ok()
+                    .and_then(|output| decode_uint(&output))
+                    .is_some_and(|balance| balance >= 1)
+            } else {
+                true
+            };
+            if received {
+                record(
+                    "transfer(address,uint256)",
+                    CheckOutcome::Passed,
+                    format!("sent 1 to {}", recipient),
+                );
+            } else {
+                record(
+                    "transfer(address,uint256)",
+                    CheckOutcome::Failed,
+                    "balance of the recipient did not increase".to_string(),
+                );
+            }
+        }
+        Ok(checks)
+    }
+}
+
+// Canonical signature of an ABI function entry, i.e. "transfer(address,uint256)"
+fn abi_signature(function: &serde_json::Value) -> String {
+    let input_types: Vec<&str> = function["inputs"]
+        .as_array()
+        .map(|inputs| {
+            inputs
+                .iter()
+                .map(|input| input["type"].as_str().unwrap_or_default())
+                .collect()
+        })
+        .unwrap_or_default();
+    format!(
+        "{}({})",
+        function["name"].as_str().unwrap_or_default(),
+        input_types.join(",")
+    )
+}
+
+#[async_trait]
+impl SpecialFunctions for AgentSmartContractDeveloper {
+    fn get_attributes_from_agent(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn set_agent_state(&mut self, state: AgentState) {
+        self.attributes.state = state;
+    }
+
+    fn requires(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ProjectDescription,
+            FactSheetField::ProjectScope,
+        ]
+    }
+
+    fn produces(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ContractCode,
+            FactSheetField::SmartContracts,
+            FactSheetField::ContractChecks,
+        ]
+    }
+
+    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        let is_blockchain_required: bool = factsheet
+            .project_scope
+            .map(|scope| scope.is_blockchain_required)
+            .unwrap_or(false);
+        if !is_blockchain_required {
+            PrintCommand::AICall.print_agent_message(
+                self.attributes.position.as_str(),
+                "No smart contracts required for this project",
+            );
+            self.attributes.state = AgentState::Finished;
+        }
+
+        while self.attributes.state != AgentState::Finished {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    self.call_initial_contract_code(factsheet).await?;
+                    self.attributes.state = AgentState::Working;
+                    continue;
+                }
+
+                AgentState::Working => {
+                    if self.bug_count == 0 {
+                        self.call_token_transaction_code(factsheet).await?;
+                    } else {
+                        self.call_fix_contract_bugs(factsheet).await?;
+                    }
+                    self.attributes.state = AgentState::UnitTesting;
+                    continue;
+                }
+
+                AgentState::UnitTesting => {
+                    PrintCommand::UnitTest.print_agent_message(
+                        self.attributes.position.as_str(),
+                        "Contract Unit Testing: compiling contracts with solc...",
+                    );
+
+                    let contract_code: String = factsheet.contract_code.clone().unwrap_or_default();
+                    let tested: Result<Vec<SmartContract>, String> =
+                        match self.compile_contracts(&contract_code)? {
+                            Ok(contracts) => {
+                                let compiled_msg: String = format!(
+                                    "Contract Unit Testing: compiled {} contracts...",
+                                    contracts.len()
+                                );
+                                PrintCommand::UnitTest.print_agent_message(
+                                    self.attributes.position.as_str(),
+                                    compiled_msg.as_str(),
+                                );
+                                self.test_on_dev_chain(contracts, factsheet).await?
+                            }
+                            Err(errors) => Err(errors),
+                        };
+
+                    match tested {
+                        Ok(contracts) => {
+                            self.bug_count = 0;
+                            factsheet.smart_contracts = Some(contracts);
+                            self.attributes.state = AgentState::Finished;
+                        }
+                        Err(errors) => {
+                            // Update error stats
+                            self.bug_count += 1;
+                            self.bug_errors = Some(errors);
+
+                            // Exit if too many bugs
+                            if self.bug_count > 2 {
+                                PrintCommand::Issue.print_agent_message(
+                                    self.attributes.position.as_str(),
+                                    "Contract Unit Testing: Too many bugs found in contracts",
+                                );
+                                return Err(WizError::BuildFailure {
+                                    attempts: self.bug_count,
+                                    errors: self.bug_errors.clone().unwrap_or_default(),
+                                });
+                            }
+
+                            // Pass back for rework
+                            self.attributes.state = AgentState::Working;
+                        }
+                    }
+                }
+
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::dev_chain::spawn_fake_dev_chain;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::helpers::general::copy_project_template;
+    use crate::helpers::sandbox::Sandbox;
+    use crate::helpers::test_support::temp_workspace_config;
+    use crate::models::agents::agent_traits::ProjectScope;
+
+    // Stands in for solc: fails while the source contains BROKEN, otherwise writes MyToken artifacts
+    // The bytecode is left empty so no dev chain is needed
+    const FAKE_SOLC: &str = r#"#!/bin/sh
+for arg in "$@"; do source="$arg"; done
+while [ "$1" != "-o" ]; do shift; done
+out="$2"
+if grep -q BROKEN "$source"; then
+  echo "Error: Expected ';' but got identifier" >&2
+  exit 1
+fi
+mkdir -p "$out"
+echo '[{"type":"function","name":"mint","inputs":[],"outputs":[]}]' > "$out/MyToken.abi"
+: > "$out/MyToken.bin"
+"#;
+
+    fn test_config(name: &str) -> (PathBuf, Arc<WizConfig>) {
+        let (output_dir, mut config) = temp_workspace_config(&format!("contract_{}", name));
+
+        let solc_path: PathBuf = output_dir.join("fake-solc");
+        fs::write(&solc_path, FAKE_SOLC).unwrap();
+        Command::new("chmod")
+            .arg("+x")
+            .arg(&solc_path)
+            .status()
+            .unwrap();
+
+        config.contracts.solc = solc_path;
+        (output_dir, Arc::new(config))
+    }
+
+    fn test_factsheet(is_blockchain_required: bool) -> FactSheet {
+        FactSheet {
+            project_description: "build a website where users can mint and send reward tokens"
+                .to_string(),
+            project_scope: Some(ProjectScope {
+                is_crud_required: false,
+                is_user_login_and_logout: false,
+                is_external_urls_required: false,
+                is_blockchain_required,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_contract_developer_fixes_compiler_errors() {
+        let contract_fixture: &str =
+            include_str!("../../../fixtures/llm/print_smart_contract_code.txt");
+        let fixed_fixture: &str =
+            include_str!("../../../fixtures/llm/print_fixed_contract_code.txt");
+        let provider: Arc<MockProvider> = Arc::new(
+            MockProvider::new()
+                .with_response("print_smart_contract_code", contract_fixture)
+                .with_response("print_fixed_contract_code", fixed_fixture)
+                .with_response(
+                    "print_token_transaction_code",
+                    "```solidity\ncontract MyToken { BROKEN }\n```",
+                ),
+        );
+        let (output_dir, config) = test_config("fixes");
+        let mut agent: AgentSmartContractDeveloper =
+            AgentSmartContractDeveloper::new(provider.clone(), config.clone());
+
+        let mut factsheet: FactSheet = test_factsheet(true);
+        agent
+            .execute(&mut factsheet)
+            .await
+            .expect("Failed to execute Smart Contract Developer agent");
+
+        // The broken contract went back through the fix prompt instead of being regenerated
+        let calls: Vec<String> = provider.calls();
+        assert_eq!(
+            calls,
+            vec![
+                "print_smart_contract_code",
+                "print_token_transaction_code",
+                "print_fixed_contract_code"
+            ]
+        );
+
+        let contracts: Vec<SmartContract> = factsheet.smart_contracts.unwrap();
+        assert_eq!(contracts.len(), 1);
+        assert_eq!(contracts[0].name, "MyToken");
+        assert_eq!(contracts[0].bytecode, None);
+        assert!(factsheet.contract_checks.is_none());
+        assert_eq!(
+            contracts[0].source_path,
+            config.contracts_path().join("MyToken.sol")
+        );
+        assert!(contracts[0].source_path.is_file());
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_contract_developer_runs_beside_backend_sandboxes() {
+        let contract_fixture: &str =
+            include_str!("../../../fixtures/llm/print_smart_contract_code.txt");
+        let provider: Arc<MockProvider> = Arc::new(
+            MockProvider::new()
+                .with_response("print_smart_contract_code", contract_fixture)
+                .with_response("print_token_transaction_code", contract_fixture),
+        );
+        let (output_dir, config) = test_config("beside_backend");
+        let repo_config: WizConfig = WizConfig::resolve(None).expect("Failed to load wizai.toml");
+        copy_project_template(
+            &repo_config.web_server_project_path(),
+            &config.web_server_project_path(),
+        )
+        .unwrap();
+        let mut agent: AgentSmartContractDeveloper =
+            AgentSmartContractDeveloper::new(provider, config.clone());
+
+        // The Backend Developer copies the server project into sandboxes while the contracts
+        // are written and compiled in the same workspace
+        let backend_config: Arc<WizConfig> = config.clone();
+        let backend = tokio::task::spawn_blocking(move || {
+            (0..20)
+                .map(|_| {
+                    Sandbox::create(&backend_config)
+                        .map(|sandbox: Sandbox| sandbox.project_path().join("contracts").exists())
+                })
+                .collect::<Result<Vec<bool>, WizError>>()
+        });
+
+        let mut factsheet: FactSheet = test_factsheet(true);
+        agent
+            .execute(&mut factsheet)
+            .await
+            .expect("Failed to execute Smart Contract Developer agent");
+        let copied_contracts: Vec<bool> = backend
+            .await
+            .unwrap()
+            .expect("Every sandbox should be created");
+
+        assert!(copied_contracts.iter().all(|copied: &bool| !copied));
+        assert!(config.contracts_path().join("MyToken.sol").is_file());
+        assert_eq!(factsheet.smart_contracts.unwrap().len(), 1);
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_contract_developer_skips_projects_without_blockchain() {
+        let (output_dir, config) = test_config("skips");
+        let mut agent: AgentSmartContractDeveloper =
+            AgentSmartContractDeveloper::new(Arc::new(MockProvider::new()), config);
+
+        let mut factsheet: FactSheet = test_factsheet(false);
+        agent
+            .execute(&mut factsheet)
+            .await
+            .expect("Nothing to do without blockchain");
+        assert!(factsheet.smart_contracts.is_none());
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_contract_checks_on_dev_chain() {
+        let dev_chain: DevChain = DevChain::connect(&spawn_fake_dev_chain().await).unwrap();
+        let abi: serde_json::Value = serde_json::json!([
+            {"type": "constructor", "inputs": []},
+            {"type": "function", "name": "totalSupply", "stateMutability": "view", "inputs": []},
+            {"type": "function", "name": "broken", "stateMutability": "view", "inputs": []},
+            {"type": "function", "name": "balanceOf", "stateMutability": "view",
+             "inputs": [{"name": "owner", "type": "address"}]},
+            {"type": "function", "name": "transfer", "stateMutability": "nonpayable",
+             "inputs": [{"name": "to", "type": "address"}, {"name": "value", "type": "uint256"}]}
+        ]);
+        let mut contracts: Vec<SmartContract> = vec![
+            SmartContract {
+                name: "MyToken".to_string(),
+                source_path: PathBuf::from("contracts/MyToken.sol"),
+                abi,
+                bytecode: Some("6080604052".to_string()),
+                address: None,
+            },
+            SmartContract {
+                name: "IToken".to_string(),
+                source_path: PathBuf::from("contracts/MyToken.sol"),
+                abi: serde_json::json!([]),
+                bytecode: None,
+                address: None,
+            },
+        ];
+
+        let checks: Vec<ContractCheck> =
+            AgentSmartContractDeveloper::run_contract_checks(&dev_chain, &mut contracts)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            contracts[0].address.as_deref(),
+            Some("0x5fbdb2315678afecb367f032d93f642f64180aa3")
+        );
+        assert_eq!(contracts[1].address, None);
+
+        let outcomes: Vec<(&str, CheckOutcome)> = checks
+            .iter()
+            .map(|check: &ContractCheck| (check.check.as_str(), check.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("deploy", CheckOutcome::Passed),
+                ("call totalSupply()", CheckOutcome::Passed),
+                ("call broken()", CheckOutcome::Failed),
+                ("transfer(address,uint256)", CheckOutcome::Passed),
+            ]
+        );
+    }
+
+    #[test]
+    fn tests_contract_name() {
+        let code: &str = "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.0;\n\nabstract contract Ownable {}\ncontract RewardToken is Ownable {}";
+        assert_eq!(
+            AgentSmartContractDeveloper::contract_name(code),
+            Some("RewardToken".to_string())
+        );
+        let code: &str = "pragma solidity ^0.8.0;\ninterface IToken {}\ncontract Vault {}\ncontract RewardToken is Vault, IToken {}\nlibrary SafeMath {}";
+        assert_eq!(
+            AgentSmartContractDeveloper::contract_name(code),
+            Some("RewardToken".to_string())
+        );
+        assert_eq!(
+            AgentSmartContractDeveloper::contract_name("pragma solidity ^0.8.0;"),
+            None
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use crate::ai_functions::aifunc_frontend::{
+    print_fixed_frontend_code, print_frontend_ui_components, print_frontend_webapp_code,
+    print_improved_frontend_webapp_code,
+};
+use crate::apis::llm_provider::LlmProvider;
+use crate::helpers::code_fence::{extract_code, HTML_TAGS};
+use crate::helpers::command_line::PrintCommand;
+use crate::helpers::config::WizConfig;
+use crate::helpers::general::{
+    ai_task_request, ai_task_request_decoded, read_frontend_template_contents, save_frontend_code,
+};
+use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
+use crate::models::agents::agent_traits::{
+    FactSheet, FactSheetField, RouteObject, SpecialFunctions, UiComponent,
+};
+use crate::models::general::error::WizError;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+// Frontend Developer
+// Writes a single page web app which calls the endpoints of the generated backend
+#[derive(Debug)]
+pub struct AgentFrontendDeveloper {
+    attributes: BasicAgent,
+    bug_errors: Option<String>,
+    bug_count: u8,
+    provider: Arc<dyn LlmProvider>,
+    config: Arc<WizConfig>,
+}
+
+impl AgentFrontendDeveloper {
+    pub const POSITION: &'static str = "Frontend Developer";
+
+    pub fn new(provider: Arc<dyn LlmProvider>, config: Arc<WizConfig>) -> Self {
+        let attributes: BasicAgent = BasicAgent {
+            objective: "Develops frontend code for the web app using the backend api endpoints"
+                .to_string(),
+            position: Self::POSITION.to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes,
+            bug_errors: None,
+            bug_count: 0,
+            provider,
+            config,
+        }
+    }
+
+    // Endpoints the frontend has to be wired to, as JSON for the prompts
+    fn api_endpoints_context(factsheet: &FactSheet) -> String {
+        let api_endpoints: &[RouteObject] = factsheet.api_endpoint_schema.as_deref().unwrap_or(&[]);
+        serde_json::to_string(api_endpoints).expect("RouteObject is always serializable to JSON")
+    }
+
+    async fn call_initial_frontend_code(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), WizError> {
+        let code_template_str: String = read_frontend_template_contents(&self.config)?;
+
+        // Concatenate Instruction
+        let msg_context: String = format!(
+            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n API_ENDPOINTS: {} \n BACKEND_ADDRESS: call the endpoints at {} \n",
+            code_template_str,
+            factsheet.project_description,
+            Self::api_endpoints_context(factsheet),
+            self.config.server_url("")
+        );
+
+        let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_frontend_webapp_code),
+            print_frontend_webapp_code,
+        )
+        .await?;
+
+        self.store_frontend_code(factsheet, &ai_response)
+    }
+
+    async fn call_improved_frontend_code(
+        &mut self,
+        factsheet: &mut FactSheet,
+    ) -> Result<(), WizError> {
+        let msg_context: String = format!(
+            "CODE TEMPLATE: {:?} \n PROJECT_DESCRIPTION: {} \n API_ENDPOINTS: {} \n BACKEND_ADDRESS: call the endpoints at {} \n",
+            factsheet.frontend_code,
+            factsheet.project_description,
+            Self::api_endpoints_context(factsheet),
+            self.config.server_url("")
+        );
+
+        let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_improved_frontend_webapp_code),
+            print_improved_frontend_webapp_code,
+        )
+        .await?;
+
+        self.store_frontend_code(factsheet, &ai_response)
+    }
+
+    async fn call_fix_code_bugs(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        let msg_context: String = format!(
+            "BROKEN_CODE: {:?} \n ERROR_BUGS: {:?} \n
+      THIS FUNCTION ONLY OUTPUTS CODE. JUST OUTPUT THE CODE.",
+            factsheet.frontend_code, self.bug_errors
+        );
+
+        let ai_response: String = ai_task_request(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_fixed_frontend_code),
+            print_fixed_frontend_code,
+        )
+        .await?;
+
+        self.store_frontend_code(factsheet, &ai_response)
+    }
+
+    async fn call_extract_ui_components(
+        &self,
+        frontend_code: &str,
+    ) -> Result<Vec<UiComponent>, WizError> {
+        let msg_context: String = format!("CODE_INPUT: {}", frontend_code);
+
+        ai_task_request_decoded::<Vec<UiComponent>>(
+            self.provider.as_ref(),
+            msg_context,
+            &self.attributes.position,
+            get_function_string!(print_frontend_ui_components),
+            print_frontend_ui_components,
+        )
+        .await
+    }
+
+    fn store_frontend_code(
+        &self,
+        factsheet: &mut FactSheet,
+        ai_response: &str,
+    ) -> Result<(), WizError> {
+        let frontend_code: String = extract_code(ai_response, &HTML_TAGS);
+        save_frontend_code(&self.config, &frontend_code)?;
+        factsheet.frontend_code = Some(frontend_code);
+        Ok(())
+    }
+
+    // Static checks of the page: a complete html document calling every backend endpoint
+    pub fn find_frontend_bugs(frontend_code: &str, api_endpoints: &[RouteObject]) -> Vec<String> {
+        let mut bugs: Vec<String> = vec![];
+
+        let lower_code: String = frontend_code.to_lowercase();
+        if !lower_code.contains("<html") || !lower_code.contains("</html>") {
+            bugs.push("The code is not a complete html document".to_string());
+        }
+
+        for endpoint in api_endpoints {
+            // Dynamic routes are matched on the part before the first parameter
+            let route_prefix: &str = endpoint
+                .route
+                .split(['{', ':'])
+                .next()
+                .unwrap_or("")
+                .trim_end_matches('/');
+
+            if !route_prefix.is_empty() && !frontend_code.contains(route_prefix) {
+                bugs.push(format!(
+                    "The backend endpoint {} {} is never called",
+                    endpoint.method, endpoint.route
+                ));
+            }
+        }
+        bugs
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for AgentFrontendDeveloper {
+    fn get_attributes_from_agent(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn set_agent_state(&mut self, state: AgentState) {
+        self.attributes.state = state;
+    }
+
+    fn requires(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::ProjectDescription,
+            FactSheetField::ApiEndpointSchema,
+        ]
+    }
+
+    fn produces(&self) -> Vec<FactSheetField> {
+        vec![
+            FactSheetField::FrontendCode,
+            FactSheetField::FrontendComponents,
+        ]
+    }
+
+    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        while self.attributes.state != AgentState::Finished {
+            match &self.attributes.state {
+                AgentState::Discovery => {
+                    self.call_initial_frontend_code(factsheet).await?;
+                    self.attributes.state = AgentState::Working;
+                    continue;
+                }
+
+                AgentState::Working => {
+                    if self.bug_count == 0 {
+                        self.call_improved_frontend_code(factsheet).await?;
+                    } else {
+                        self.call_fix_code_bugs(factsheet).await?;
+                    }
+                    self.attributes.state = AgentState::UnitTesting;
+                    continue;
+                }
+
+                AgentState::UnitTesting => {
+                    PrintCommand::UnitTest.print_agent_message(
+                        self.attributes.position.as_str(),
+                        "Frontend Code Unit Testing: checking the page against the api endpoints...",
+                    );
+
+                    let frontend_code: String = factsheet.frontend_code.clone().unwrap_or_default();
+                    let bugs: Vec<String> = Self::find_frontend_bugs(
+                        &frontend_code,
+                        factsheet.api_endpoint_schema.as_deref().unwrap_or(&[]),
+                    );
+
+                    if !bugs.is_empty() {
+                        // Update error stats
+                        self.bug_count += 1;
+                        self.bug_errors = Some(bugs.join("\n"));
+
+                        // Exit if too many bugs
+                        if self.bug_count > 2 {
+                            PrintCommand::Issue.print_agent_message(
+                                self.attributes.position.as_str(),
+                                "Frontend Code Unit Testing: Too many bugs found in code",
+                            );
+                            return Err(WizError::BuildFailure {
+                                attempts: self.bug_count,
+                                errors: self.bug_errors.clone().unwrap_or_default(),
+                            });
+                        }
+
+                        // Pass back for rework
+                        self.attributes.state = AgentState::Working;
+                        continue;
+                    }
+
+                    self.bug_count = 0;
+                    PrintCommand::UnitTest.print_agent_message(
+                        self.attributes.position.as_str(),
+                        "Frontend Code Unit Testing: every api endpoint is wired up...",
+                    );
+
+                    let ui_components: Vec<UiComponent> =
+                        self.call_extract_ui_components(&frontend_code).await?;
+                    factsheet.frontend_components = Some(ui_components);
+
+                    self.attributes.state = AgentState::Finished;
+                }
+
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::helpers::test_support::temp_workspace_config;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn test_factsheet() -> FactSheet {
+        let api_endpoints: Vec<RouteObject> = serde_json::from_str(include_str!(
+            "../../../fixtures/llm/print_rest_api_endpoints.txt"
+        ))
+        .unwrap();
+
+        FactSheet {
+            project_description: "build a website that tracks fitness workouts".to_string(),
+            api_endpoint_schema: Some(api_endpoints),
+            ..Default::default()
+        }
+    }
+
+    fn test_config(name: &str) -> (PathBuf, Arc<WizConfig>) {
+        let (output_dir, config) = temp_workspace_config(&format!("frontend_{}", name));
+        (output_dir, Arc::new(config))
+    }
+
+    #[tokio::test]
+    async fn tests_frontend_developer() {
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+        let (output_dir, config) = test_config("developer");
+        let mut agent: AgentFrontendDeveloper =
+            AgentFrontendDeveloper::new(Arc::new(provider), config.clone());
+
+        let mut factsheet: FactSheet = test_factsheet();
+        agent
+            .execute(&mut factsheet)
+            .await
+            .expect("Failed to execute Frontend Developer agent");
+
+        let frontend_code: String = factsheet.frontend_code.unwrap();
+        assert!(frontend_code.starts_with("<!DOCTYPE html>"));
+        assert_eq!(
+            fs::read_to_string(config.frontend_output_path()).unwrap(),
+            frontend_code
+        );
+        assert_eq!(factsheet.frontend_components.unwrap().len(), 3);
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_frontend_developer_fixes_missing_endpoints() {
+        let fixed_code: &str = include_str!("../../../fixtures/llm/print_fixed_frontend_code.txt");
+        let provider: Arc<MockProvider> = Arc::new(
+            MockProvider::new()
+                .with_response("print_frontend_webapp_code", "<html><body></body></html>")
+                .with_response(
+                    "print_improved_frontend_webapp_code",
+                    "<html><script>fetch('/time')</script></html>",
+                )
+                .with_response("print_fixed_frontend_code", fixed_code)
+                .with_response("print_frontend_ui_components", "[]"),
+        );
+        let (output_dir, config) = test_config("fixes");
+        let mut agent: AgentFrontendDeveloper =
+            AgentFrontendDeveloper::new(provider.clone(), config);
+
+        let mut factsheet: FactSheet = test_factsheet();
+        agent
+            .execute(&mut factsheet)
+            .await
+            .expect("Failed to execute Frontend Developer agent");
+
+        assert!(provider
+            .calls()
+            .contains(&"print_fixed_frontend_code".to_string()));
+        assert!(factsheet.frontend_code.unwrap().contains("/workout/${id}"));
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn tests_find_frontend_bugs() {
+        let api_endpoints: Vec<RouteObject> = test_factsheet().api_endpoint_schema.unwrap();
+
+        let bugs: Vec<String> = AgentFrontendDeveloper::find_frontend_bugs(
+            "<html><script>fetch('/workout')</script></html>",
+            &api_endpoints,
+        );
+        assert_eq!(bugs, vec!["The backend endpoint GET /time is never called"]);
+
+        let bugs: Vec<String> =
+            AgentFrontendDeveloper::find_frontend_bugs("fetch('/workout'); fetch('/time')", &[]);
+        assert_eq!(bugs, vec!["The code is not a complete html document"]);
+    }
+}
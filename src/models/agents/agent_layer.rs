@@ -0,0 +1,215 @@
+use crate::ai_functions::aifunc_backend::{print_auth_layer_code, print_persistence_layer_code};
+use crate::apis::llm_provider::LlmProvider;
+use crate::helpers::code_fence::{extract_code, RUST_TAGS};
+use crate::helpers::command_line::PrintCommand;
+use crate::helpers::config::WizConfig;
+use crate::helpers::general::{
+    ai_task_request, read_auth_template_contents, read_persistence_template_contents,
+    save_auth_code, save_persistence_code,
+};
+use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
+use crate::models::agents::agent_traits::{FactSheet, FactSheetField, SpecialFunctions};
+use crate::models::general::error::WizError;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+// Modules of the web server which are only generated when the project scope needs them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendLayer {
+    Persistence,
+    Auth,
+}
+
+impl BackendLayer {
+    pub fn position(&self) -> &'static str {
+        match self {
+            BackendLayer::Persistence => "Persistence Developer",
+            BackendLayer::Auth => "Auth Developer",
+        }
+    }
+
+    // Name of the module next to main.rs
+    pub fn module_name(&self) -> &'static str {
+        match self {
+            BackendLayer::Persistence => "persistence",
+            BackendLayer::Auth => "auth",
+        }
+    }
+
+    pub fn field(&self) -> FactSheetField {
+        match self {
+            BackendLayer::Persistence => FactSheetField::PersistenceCode,
+            BackendLayer::Auth => FactSheetField::AuthCode,
+        }
+    }
+
+    pub fn code<'a>(&self, factsheet: &'a FactSheet) -> Option<&'a String> {
+        match self {
+            BackendLayer::Persistence => factsheet.persistence_code.as_ref(),
+            BackendLayer::Auth => factsheet.auth_code.as_ref(),
+        }
+    }
+}
+
+// Persistence or Auth Developer
+// Adapts the template of one backend layer before the Backend Developer writes main.rs
+#[derive(Debug)]
+pub struct AgentLayerDeveloper {
+    attributes: BasicAgent,
+    layer: BackendLayer,
+    provider: Arc<dyn LlmProvider>,
+    config: Arc<WizConfig>,
+}
+
+impl AgentLayerDeveloper {
+    pub fn new(
+        layer: BackendLayer,
+        provider: Arc<dyn LlmProvider>,
+        config: Arc<WizConfig>,
+    ) -> Self {
+        let objective: &str = match layer {
+            BackendLayer::Persistence => "Develops the json file storage of the webserver",
+            BackendLayer::Auth => "Develops user registration, login and logout of the webserver",
+        };
+        let attributes: BasicAgent = BasicAgent {
+            objective: objective.to_string(),
+            position: layer.position().to_string(),
+            state: AgentState::Discovery,
+        };
+
+        Self {
+            attributes,
+            layer,
+            provider,
+            config,
+        }
+    }
+
+    async fn call_layer_code(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        let code_template_str: String = match self.layer {
+            BackendLayer::Persistence => read_persistence_template_contents(&self.config)?,
+            BackendLayer::Auth => read_auth_template_contents(&self.config)?,
+        };
+
+        let msg_context: String = format!(
+            "CODE TEMPLATE: {} \n PROJECT_DESCRIPTION: {} \n",
+            code_template_str, factsheet.project_description
+        );
+
+        let ai_response: String = match self.layer {
+            BackendLayer::Persistence => {
+                ai_task_request(
+                    self.provider.as_ref(),
+                    msg_context,
+                    &self.attributes.position,
+                    get_function_string!(print_persistence_layer_code),
+                    print_persistence_layer_code,
+                )
+                .await?
+            }
+            BackendLayer::Auth => {
+                ai_task_request(
+                    self.provider.as_ref(),
+                    msg_context,
+                    &self.attributes.position,
+                    get_function_string!(print_auth_layer_code),
+                    print_auth_layer_code,
+                )
+                .await?
+            }
+        };
+
+        let layer_code: String = extract_code(&ai_response, &RUST_TAGS);
+        match self.layer {
+            BackendLayer::Persistence => {
+                save_persistence_code(&self.config, &layer_code)?;
+                factsheet.persistence_code = Some(layer_code);
+            }
+            BackendLayer::Auth => {
+                save_auth_code(&self.config, &layer_code)?;
+                factsheet.auth_code = Some(layer_code);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SpecialFunctions for AgentLayerDeveloper {
+    fn get_attributes_from_agent(&self) -> &BasicAgent {
+        &self.attributes
+    }
+
+    fn set_agent_state(&mut self, state: AgentState) {
+        self.attributes.state = state;
+    }
+
+    fn requires(&self) -> Vec<FactSheetField> {
+        vec![FactSheetField::ProjectDescription]
+    }
+
+    fn produces(&self) -> Vec<FactSheetField> {
+        vec![self.layer.field()]
+    }
+
+    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+        if self.attributes.state != AgentState::Finished {
+            self.call_layer_code(factsheet).await?;
+
+            // The module is compiled together with main.rs by the Backend Developer
+            PrintCommand::UnitTest.print_agent_message(
+                self.attributes.position.as_str(),
+                "Module written, it is built together with the web server...",
+            );
+            self.attributes.state = AgentState::Finished;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::helpers::test_support::temp_workspace_config;
+    use std::fs;
+
+    #[tokio::test]
+    async fn tests_layer_developer_writes_its_module() {
+        let (output_dir, config) = temp_workspace_config("layer_writes_its_module");
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+        let mut agent: AgentLayerDeveloper = AgentLayerDeveloper::new(
+            BackendLayer::Auth,
+            Arc::new(provider),
+            Arc::new(config.clone()),
+        );
+
+        let mut factsheet: FactSheet = serde_json::from_str(
+            r#"{
+              "project_description": "build a todo app with user accounts",
+              "project_scope": null,
+              "external_urls": null,
+              "backend_code": null,
+              "api_endpoint_schema": null
+            }"#,
+        )
+        .unwrap();
+
+        agent
+            .execute(&mut factsheet)
+            .await
+            .expect("Failed to execute Auth Developer agent");
+
+        let auth_code: String = factsheet.auth_code.clone().expect("auth code stored");
+        assert!(auth_code.contains("pub fn configure"));
+        assert_eq!(
+            fs::read_to_string(config.auth_module_path()).unwrap(),
+            auth_code
+        );
+        assert!(factsheet.persistence_code.is_none());
+
+        let _ = fs::remove_dir_all(&output_dir);
+    }
+}
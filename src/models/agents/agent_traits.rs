@@ -1,41 +1,179 @@
-use crate::models::agent_basic::basic_agent::BasicAgent;
+use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
+use crate::models::general::error::WizError;
+use crate::models::general::route::{
+    deserialize_flag, flag_schema, path_parameter, FieldType, HttpMethod,
+};
 use async_trait::async_trait;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 pub struct RouteObject {
-    pub is_route_dynamic: String,
-    pub method: String,
-    pub request_body: serde_json::Value,
-    pub response: serde_json::Value,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    #[schemars(schema_with = "flag_schema")]
+    pub is_route_dynamic: bool,
+    pub method: HttpMethod,
+    #[serde(default)]
+    pub request_body: FieldType,
+    #[serde(default)]
+    pub response: FieldType,
     pub route: String,
+    // Types of the path parameters, known when the route was read from the code
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub path_types: BTreeMap<String, FieldType>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+impl RouteObject {
+    // i.e. ["id"] for /task/{id}
+    pub fn path_parameters(&self) -> Vec<&str> {
+        self.route.split('/').filter_map(path_parameter).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Default)]
 pub struct ProjectScope {
     pub is_crud_required: bool,
     pub is_user_login_and_logout: bool,
     pub is_external_urls_required: bool,
+    #[serde(default)]
+    pub is_blockchain_required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+pub struct UiComponent {
+    pub component: String,
+    pub props: serde_json::Value,
+    pub state: serde_json::Value,
+}
+
+// A compiled Solidity contract
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SmartContract {
+    pub name: String,
+    pub source_path: PathBuf,
+    pub abi: serde_json::Value,
+    // Hex encoded creation bytecode, None for interfaces and abstract contracts
+    pub bytecode: Option<String>,
+    // Where it was deployed on the local dev chain during testing
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckOutcome {
+    Passed,
+    Failed,
+    Skipped,
 }
 
+// A check run against a contract deployed on the local dev chain
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContractCheck {
+    pub contract: String,
+    pub check: String,
+    pub outcome: CheckOutcome,
+    pub detail: String,
+}
+
+// Generated ethers-rs module of the web server, i.e. src/blockchain/wallet.rs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BlockchainModule {
+    pub name: String,
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct FactSheet {
     pub project_description: String,
     pub project_scope: Option<ProjectScope>,
     pub external_urls: Option<Vec<String>>,
     pub backend_code: Option<String>,
+    #[serde(default)]
+    pub persistence_code: Option<String>,
+    #[serde(default)]
+    pub auth_code: Option<String>,
     pub api_endpoint_schema: Option<Vec<RouteObject>>,
+    #[serde(default)]
+    pub frontend_code: Option<String>,
+    #[serde(default)]
+    pub frontend_components: Option<Vec<UiComponent>>,
+    #[serde(default)]
+    pub contract_code: Option<String>,
+    #[serde(default)]
+    pub smart_contracts: Option<Vec<SmartContract>>,
+    #[serde(default)]
+    pub contract_checks: Option<Vec<ContractCheck>>,
+    #[serde(default)]
+    pub blockchain_modules: Option<Vec<BlockchainModule>>,
+}
+
+// Parts of the factsheet agents read and write, used to schedule them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FactSheetField {
+    ProjectDescription,
+    ProjectScope,
+    ExternalUrls,
+    BackendCode,
+    PersistenceCode,
+    AuthCode,
+    ApiEndpointSchema,
+    FrontendCode,
+    FrontendComponents,
+    ContractCode,
+    SmartContracts,
+    ContractChecks,
+    BlockchainModules,
+}
+
+impl FactSheet {
+    // Copy one field over from the factsheet an agent worked on
+    pub fn merge_field(&mut self, from: &FactSheet, field: FactSheetField) {
+        match field {
+            FactSheetField::ProjectDescription => {
+                self.project_description = from.project_description.clone()
+            }
+            FactSheetField::ProjectScope => self.project_scope = from.project_scope,
+            FactSheetField::ExternalUrls => self.external_urls = from.external_urls.clone(),
+            FactSheetField::BackendCode => self.backend_code = from.backend_code.clone(),
+            FactSheetField::PersistenceCode => {
+                self.persistence_code = from.persistence_code.clone()
+            }
+            FactSheetField::AuthCode => self.auth_code = from.auth_code.clone(),
+            FactSheetField::ApiEndpointSchema => {
+                self.api_endpoint_schema = from.api_endpoint_schema.clone()
+            }
+            FactSheetField::FrontendCode => self.frontend_code = from.frontend_code.clone(),
+            FactSheetField::FrontendComponents => {
+                self.frontend_components = from.frontend_components.clone()
+            }
+            FactSheetField::ContractCode => self.contract_code = from.contract_code.clone(),
+            FactSheetField::SmartContracts => self.smart_contracts = from.smart_contracts.clone(),
+            FactSheetField::ContractChecks => self.contract_checks = from.contract_checks.clone(),
+            FactSheetField::BlockchainModules => {
+                self.blockchain_modules = from.blockchain_modules.clone()
+            }
+        }
+    }
 }
 
 #[async_trait]
-pub trait SpecialFunctions: Debug {
+pub trait SpecialFunctions: Debug + Send {
     // Used to that manager can get attributes from Agents
     fn get_attributes_from_agent(&self) -> &BasicAgent;
 
+    // Used when resuming from a checkpoint
+    fn set_agent_state(&mut self, state: AgentState);
+
+    // Factsheet fields which have to be filled in before the agent can start
+    fn requires(&self) -> Vec<FactSheetField>;
+
+    // Factsheet fields the agent fills in
+    fn produces(&self) -> Vec<FactSheetField>;
+
     // This function will allow agents to execute their logic
-    async fn execute(
-        &mut self,
-        factsheet: &mut FactSheet,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+    async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError>;
 }
@@ -1,3 +1,7 @@
 pub mod agent_architect;
 pub mod agent_backend;
+pub mod agent_blockchain;
+pub mod agent_contract;
+pub mod agent_frontend;
+pub mod agent_layer;
 pub mod agent_traits;
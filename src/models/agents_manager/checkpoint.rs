@@ -0,0 +1,77 @@
+use crate::models::agent_basic::basic_agent::AgentState;
+use crate::models::agents::agent_traits::FactSheet;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AgentCheckpoint {
+    pub position: String,
+    pub state: AgentState,
+}
+
+// Progress of a run, saved after every agent so a later run can resume it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub factsheet: FactSheet,
+    pub agents: Vec<AgentCheckpoint>,
+}
+
+impl Checkpoint {
+    pub fn new(factsheet: FactSheet) -> Self {
+        Self {
+            factsheet,
+            agents: vec![],
+        }
+    }
+
+    pub fn agent_state(&self, position: &str) -> Option<AgentState> {
+        self.agents
+            .iter()
+            .find(|agent: &&AgentCheckpoint| agent.position == position)
+            .map(|agent: &AgentCheckpoint| agent.state)
+    }
+
+    pub fn record_agent(&mut self, position: &str, state: AgentState) {
+        match self
+            .agents
+            .iter_mut()
+            .find(|agent: &&mut AgentCheckpoint| agent.position == position)
+        {
+            Some(agent) => agent.state = state,
+            None => self.agents.push(AgentCheckpoint {
+                position: position.to_string(),
+                state,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_record_agent_states() {
+        let factsheet: FactSheet = FactSheet {
+            project_description: "build a website that says hello".to_string(),
+            ..Default::default()
+        };
+        let mut checkpoint: Checkpoint = Checkpoint::new(factsheet);
+
+        checkpoint.record_agent("Solutions Architect", AgentState::Finished);
+        checkpoint.record_agent("Backend Developer", AgentState::Working);
+        checkpoint.record_agent("Backend Developer", AgentState::UnitTesting);
+
+        assert_eq!(checkpoint.agents.len(), 2);
+        assert_eq!(
+            checkpoint.agent_state("Backend Developer"),
+            Some(AgentState::UnitTesting)
+        );
+        assert_eq!(checkpoint.agent_state("Frontend Developer"), None);
+
+        let json: String = serde_json::to_string(&checkpoint).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Checkpoint>(&json).unwrap(),
+            checkpoint
+        );
+    }
+}
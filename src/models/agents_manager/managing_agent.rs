@@ -1,90 +1,822 @@
+use crate::helpers::command_line::PrintCommand;
 use crate::models::agent_basic::basic_agent::{AgentState, BasicAgent};
-use crate::models::agents::agent_traits::{FactSheet, SpecialFunctions};
+use crate::models::agent_basic::basic_traits::BasicTraits;
+use crate::models::agents::agent_traits::{FactSheet, FactSheetField, SpecialFunctions};
 
 use crate::ai_functions::ai_func_managing::convert_user_input_to_goal;
-use crate::helpers::general::ai_task_request;
+use crate::apis::llm_provider::LlmProvider;
+use crate::helpers::approval::{ApprovalHook, TerminalApproval};
+use crate::helpers::config::WizConfig;
+use crate::helpers::general::{ai_task_request, save_checkpoint};
 use crate::models::agents::agent_architect::AgentSolutionArchitect;
 use crate::models::agents::agent_backend::AgentBackendDeveloper;
+use crate::models::agents::agent_blockchain::AgentBlockchainIntegrator;
+use crate::models::agents::agent_contract::AgentSmartContractDeveloper;
+use crate::models::agents::agent_frontend::AgentFrontendDeveloper;
+use crate::models::agents::agent_layer::{AgentLayerDeveloper, BackendLayer};
+use crate::models::agents::agent_traits::ProjectScope;
+use crate::models::agents_manager::checkpoint::Checkpoint;
+use crate::models::agents_manager::project_plan::ProjectPlan;
+use crate::models::general::error::WizError;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinSet;
 
 #[derive(Debug)]
 pub struct ManagingAgent {
     _attributes: BasicAgent,
-    factsheet: FactSheet,
+    checkpoint: Checkpoint,
+    checkpointing: bool,
     agents: Vec<Box<dyn SpecialFunctions>>,
+    provider: Arc<dyn LlmProvider>,
+    agent_providers: HashMap<String, Arc<dyn LlmProvider>>,
+    config: Arc<WizConfig>,
+    approval: Arc<dyn ApprovalHook>,
 }
 
 impl ManagingAgent {
-    pub async fn new(usr_req: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let position: String = "Project Manager".to_string();
-
-        let attributes: BasicAgent = BasicAgent {
+    fn manager_attributes() -> BasicAgent {
+        BasicAgent {
             objective: "Manage agents who are building an excellent website for the user"
                 .to_string(),
-            position: position.clone(),
+            position: "Project Manager".to_string(),
             state: AgentState::Discovery,
-            memory: vec![],
-        };
+        }
+    }
+
+    pub async fn new(
+        usr_req: String,
+        provider: Arc<dyn LlmProvider>,
+        config: Arc<WizConfig>,
+    ) -> Result<Self, WizError> {
+        let attributes: BasicAgent = Self::manager_attributes();
 
         let project_description: String = ai_task_request(
+            provider.as_ref(),
             usr_req,
-            &position,
+            attributes.get_position(),
             get_function_string!(convert_user_input_to_goal),
             convert_user_input_to_goal,
-        ).await;
-
-        let agents: Vec<Box<dyn SpecialFunctions>> = vec![];
+        )
+        .await?;
 
         let factsheet: FactSheet = FactSheet {
             project_description,
-            project_scope: None,
-            external_urls: None,
-            backend_code: None,
-            api_endpoint_schema: None,
+            ..Default::default()
         };
 
-        Ok(Self {
-            _attributes: attributes,
-            factsheet,
-            agents,
-        })
+        Ok(Self::from_factsheet(factsheet, provider, config))
+    }
+
+    // Continue from the factsheet of a previous run, starting every agent afresh
+    pub fn from_factsheet(
+        factsheet: FactSheet,
+        provider: Arc<dyn LlmProvider>,
+        config: Arc<WizConfig>,
+    ) -> Self {
+        Self::from_checkpoint(Checkpoint::new(factsheet), provider, config)
+    }
+
+    // Continue a previous run: finished agents are skipped, the others resume in their saved state
+    pub fn from_checkpoint(
+        checkpoint: Checkpoint,
+        provider: Arc<dyn LlmProvider>,
+        config: Arc<WizConfig>,
+    ) -> Self {
+        Self {
+            _attributes: Self::manager_attributes(),
+            checkpoint,
+            checkpointing: false,
+            agents: vec![],
+            provider,
+            agent_providers: HashMap::new(),
+            config,
+            approval: Arc::new(TerminalApproval),
+        }
+    }
+
+    pub fn checkpoint(&self) -> &Checkpoint {
+        &self.checkpoint
+    }
+
+    // Save the checkpoint to the workspace after every agent
+    pub fn set_checkpointing(&mut self, checkpointing: bool) {
+        self.checkpointing = checkpointing;
+    }
+
+    // Decides whether agents may run the code they generated
+    pub fn set_approval_hook(&mut self, approval: Arc<dyn ApprovalHook>) {
+        self.approval = approval;
+    }
+
+    // Use a different provider (i.e. another model) for the agent in the given position
+    pub fn set_agent_provider(&mut self, position: &str, provider: Arc<dyn LlmProvider>) {
+        self.agent_providers.insert(position.to_string(), provider);
+    }
+
+    fn provider_for(&self, position: &str) -> Arc<dyn LlmProvider> {
+        match self.agent_providers.get(position) {
+            Some(provider) => provider.clone(),
+            None => self.provider.clone(),
+        }
     }
 
     fn add_agent(&mut self, agent: Box<dyn SpecialFunctions>) {
         self.agents.push(agent);
     }
 
-    fn create_agents(&mut self) {
-        self.add_agent(Box::new(AgentSolutionArchitect::new()));
-        self.add_agent(Box::new(AgentBackendDeveloper::new()));
+    fn create_architect(&self) -> Box<dyn SpecialFunctions> {
+        Box::new(AgentSolutionArchitect::new(
+            self.provider_for(AgentSolutionArchitect::POSITION),
+        ))
+    }
+
+    fn create_backend_developer(&self, plan: &ProjectPlan) -> Box<dyn SpecialFunctions> {
+        Box::new(
+            AgentBackendDeveloper::new(
+                self.provider_for(AgentBackendDeveloper::POSITION),
+                self.config.clone(),
+            )
+            .with_approval(self.approval.clone())
+            .with_layers(plan.layers.clone()),
+        )
+    }
+
+    fn create_layer_developer(&self, layer: BackendLayer) -> Box<dyn SpecialFunctions> {
+        Box::new(AgentLayerDeveloper::new(
+            layer,
+            self.provider_for(layer.position()),
+            self.config.clone(),
+        ))
+    }
+
+    fn create_frontend_developer(&self) -> Box<dyn SpecialFunctions> {
+        Box::new(AgentFrontendDeveloper::new(
+            self.provider_for(AgentFrontendDeveloper::POSITION),
+            self.config.clone(),
+        ))
+    }
+
+    fn create_smart_contract_developer(&self) -> Box<dyn SpecialFunctions> {
+        Box::new(AgentSmartContractDeveloper::new(
+            self.provider_for(AgentSmartContractDeveloper::POSITION),
+            self.config.clone(),
+        ))
+    }
+
+    fn create_blockchain_integrator(&self) -> Box<dyn SpecialFunctions> {
+        Box::new(
+            AgentBlockchainIntegrator::new(
+                self.provider_for(AgentBlockchainIntegrator::POSITION),
+                self.config.clone(),
+            )
+            .with_approval(self.approval.clone()),
+        )
+    }
+
+    // Plan from the scope of the Solutions Architect, an unscoped project gets the defaults
+    fn project_plan(&self) -> ProjectPlan {
+        let scope: ProjectScope = self.checkpoint.factsheet.project_scope.unwrap_or_default();
+        ProjectPlan::from_scope(&scope)
     }
 
-    pub async fn execute_project(&mut self) {
-        self.create_agents();
+    // Adds the backend layers and the Backend Developer of the plan
+    fn add_backend_agents(&mut self, plan: &ProjectPlan) {
+        for layer in &plan.layers {
+            let layer_developer: Box<dyn SpecialFunctions> = self.create_layer_developer(*layer);
+            self.add_agent(layer_developer);
+        }
+        let backend: Box<dyn SpecialFunctions> = self.create_backend_developer(plan);
+        self.add_agent(backend);
+    }
+
+    // Runs the full pipeline, only with the agents the project scope needs
+    pub async fn execute_project(&mut self) -> Result<(), WizError> {
+        self.execute_plan().await?;
+
+        let plan: ProjectPlan = self.project_plan();
+        self.add_backend_agents(&plan);
+        let frontend: Box<dyn SpecialFunctions> = self.create_frontend_developer();
+        self.add_agent(frontend);
+        if plan.is_blockchain_required {
+            let smart_contract: Box<dyn SpecialFunctions> = self.create_smart_contract_developer();
+            let blockchain: Box<dyn SpecialFunctions> = self.create_blockchain_integrator();
+            self.add_agent(smart_contract);
+            self.add_agent(blockchain);
+        }
+        self.execute_agents().await
+    }
+
+    // Stops once the Solutions Architect has scoped the project and shows the plan
+    pub async fn execute_plan(&mut self) -> Result<(), WizError> {
+        let architect: Box<dyn SpecialFunctions> = self.create_architect();
+        self.add_agent(architect);
+        self.execute_agents().await?;
+
+        self.project_plan().print();
+        Ok(())
+    }
+
+    // Only runs the Backend Developer and its planned layers against the current factsheet
+    pub async fn execute_build(&mut self) -> Result<(), WizError> {
+        let plan: ProjectPlan = self.project_plan();
+        self.add_backend_agents(&plan);
+        self.execute_agents().await
+    }
+
+    fn agent_position(agent: &Option<Box<dyn SpecialFunctions>>) -> String {
+        agent
+            .as_ref()
+            .map(|agent| agent.get_attributes_from_agent().get_position().clone())
+            .unwrap_or_default()
+    }
+
+    // Starts every pending agent whose dependencies are done and skips those whose dependencies
+    // failed, returns whether any agent changed state
+    fn start_ready_agents(
+        &self,
+        agents: &mut [Option<Box<dyn SpecialFunctions>>],
+        runs: &mut [AgentRun],
+        dependencies: &[Vec<usize>],
+        running: &mut JoinSet<AgentOutcome>,
+    ) -> bool {
+        let mut changed: bool = false;
+        for index in 0..agents.len() {
+            if runs[index] != AgentRun::Pending {
+                continue;
+            }
+            let position: String = Self::agent_position(&agents[index]);
+
+            let blocked_by: Option<usize> =
+                dependencies[index]
+                    .iter()
+                    .copied()
+                    .find(|dependency: &usize| {
+                        matches!(runs[*dependency], AgentRun::Failed | AgentRun::Skipped)
+                    });
+            if let Some(dependency) = blocked_by {
+                let skip_msg: String = format!(
+                    "Skipping {}: {} did not complete",
+                    position,
+                    Self::agent_position(&agents[dependency])
+                );
+                PrintCommand::Issue
+                    .print_agent_message(self._attributes.get_position(), skip_msg.as_str());
+                runs[index] = AgentRun::Skipped;
+                changed = true;
+                continue;
+            }
+
+            let is_ready: bool = dependencies[index]
+                .iter()
+                .all(|dependency: &usize| runs[*dependency] == AgentRun::Done);
+            if !is_ready {
+                continue;
+            }
+
+            // Resume from the checkpoint of a previous run
+            let resume_state: Option<AgentState> = self.checkpoint.agent_state(&position);
+            if resume_state == Some(AgentState::Finished) {
+                let skip_msg: String = format!("Skipping {}: finished in a previous run", position);
+                PrintCommand::AICall
+                    .print_agent_message(self._attributes.get_position(), skip_msg.as_str());
+                runs[index] = AgentRun::Done;
+                changed = true;
+                continue;
+            }
+
+            let Some(mut agent) = agents[index].take() else {
+                continue;
+            };
+            if let Some(state) = resume_state {
+                agent.set_agent_state(state);
+            }
 
-        for agent in &mut self.agents {
-            let _agent_res: Result<(), Box<dyn std::error::Error>> =
-                agent.execute(&mut self.factsheet).await;
+            let agent_info: &BasicAgent = agent.get_attributes_from_agent();
+            let start_msg: String = format!(
+                "Starting {}: {}",
+                agent_info.get_position(),
+                agent_info.get_objective()
+            );
+            PrintCommand::AICall
+                .print_agent_message(self._attributes.get_position(), start_msg.as_str());
 
-            // let agent_info: &BasicAgent = agent.get_attributes_from_agent();
-            // dbg!(agent_info);
+            let mut factsheet: FactSheet = self.checkpoint.factsheet.clone();
+            running.spawn(async move {
+                let agent_res: Result<(), WizError> = agent.execute(&mut factsheet).await;
+                (index, agent, factsheet, agent_res)
+            });
+            runs[index] = AgentRun::Running;
+            changed = true;
+        }
+        changed
+    }
+
+    // Runs the added agents as a DAG of the factsheet fields they require and produce
+    // Agents whose requirements are met run concurrently, each on its own copy of the factsheet,
+    // and their produced fields are merged back when they finish. Dependents of a failed
+    // agent are skipped and the first failure is reported. The agents are dropped afterwards.
+    async fn execute_agents(&mut self) -> Result<(), WizError> {
+        let mut agents: Vec<Option<Box<dyn SpecialFunctions>>> =
+            self.agents.drain(..).map(Some).collect();
+        let mut runs: Vec<AgentRun> = vec![AgentRun::Pending; agents.len()];
+        let mut running: JoinSet<AgentOutcome> = JoinSet::new();
+        let mut failure: Option<WizError> = None;
+
+        // Field -> agents producing it
+        let mut producers: HashMap<FactSheetField, Vec<usize>> = HashMap::new();
+        for (index, agent) in agents.iter().enumerate() {
+            for field in agent
+                .as_ref()
+                .map(|agent| agent.produces())
+                .unwrap_or_default()
+            {
+                producers.entry(field).or_default().push(index);
+            }
+        }
+
+        // Agents each agent has to wait for
+        let dependencies: Vec<Vec<usize>> = agents
+            .iter()
+            .enumerate()
+            .map(|(index, agent)| {
+                let mut dependencies: Vec<usize> = agent
+                    .as_ref()
+                    .map(|agent| agent.requires())
+                    .unwrap_or_default()
+                    .iter()
+                    .flat_map(|field: &FactSheetField| {
+                        producers.get(field).cloned().unwrap_or_default()
+                    })
+                    .filter(|producer: &usize| *producer != index)
+                    .collect();
+                dependencies.sort_unstable();
+                dependencies.dedup();
+                dependencies
+            })
+            .collect();
+
+        loop {
+            // Start or skip pending agents until nothing changes
+            while self.start_ready_agents(&mut agents, &mut runs, &dependencies, &mut running) {}
+
+            // Wait for the next agent to finish
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let (index, agent, factsheet, agent_res) = match joined {
+                Ok(outcome) => outcome,
+                // Pass on the panic of an agent
+                Err(e) => std::panic::resume_unwind(e.into_panic()),
+            };
+            let position: String = agent.get_attributes_from_agent().get_position().clone();
+
+            // Keep whatever the agent produced, even when it failed half way
+            for field in agent.produces() {
+                self.checkpoint.factsheet.merge_field(&factsheet, field);
+            }
+            self.checkpoint
+                .record_agent(&position, *agent.get_attributes_from_agent().get_state());
+            if self.checkpointing {
+                save_checkpoint(&self.config, &self.checkpoint)?;
+            }
+            agents[index] = Some(agent);
+
+            match agent_res {
+                Ok(()) => runs[index] = AgentRun::Done,
+                Err(e) => {
+                    let err_msg: String = format!("{} failed: {}", position, e);
+                    PrintCommand::Issue
+                        .print_agent_message(self._attributes.get_position(), err_msg.as_str());
+                    runs[index] = AgentRun::Failed;
+
+                    if failure.is_none() {
+                        failure = Some(WizError::Agent {
+                            position,
+                            source: Box::new(e),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Agents left pending depend on each other
+        for (index, run) in runs.iter().enumerate() {
+            if *run == AgentRun::Pending {
+                return Err(WizError::Config {
+                    message: format!(
+                        "{} is part of a dependency cycle",
+                        Self::agent_position(&agents[index])
+                    ),
+                });
+            }
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(()),
         }
     }
 }
 
+// Agent index, the agent itself, its copy of the factsheet and how it went
+type AgentOutcome = (
+    usize,
+    Box<dyn SpecialFunctions>,
+    FactSheet,
+    Result<(), WizError>,
+);
+
+// Where an agent is in the schedule of a run
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AgentRun {
+    Pending,
+    Running,
+    Done,
+    Failed,
+    Skipped,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::apis::mock_provider::MockProvider;
+    use crate::helpers::approval::ApprovalPolicy;
+    use crate::helpers::test_support::{temp_server_workspace, temp_workspace_config, ApproveAll};
+
+    fn test_config() -> Arc<WizConfig> {
+        Arc::new(WizConfig::resolve(None).expect("Failed to load wizai.toml"))
+    }
 
     #[tokio::test]
+    #[ignore = "builds and runs the generated server, its dependencies are fetched from crates.io"]
     async fn tests_managing_agent() {
         let usr_request: &str = "need a full stack app that fetches and tracks my fitness progress. Needs to include timezone info from the web.";
 
-        let mut managing_agent: ManagingAgent = ManagingAgent::new(usr_request.to_string())
+        let (output_dir, config) = temp_server_workspace("managing_full_run");
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+
+        let mut managing_agent: ManagingAgent = ManagingAgent::new(
+            usr_request.to_string(),
+            Arc::new(provider),
+            Arc::new(config),
+        )
+        .await
+        .expect("Error creating Managing Agent");
+        managing_agent.set_approval_hook(Arc::new(ApproveAll));
+
+        managing_agent
+            .execute_project()
+            .await
+            .expect("Failed to execute project");
+
+        assert!(managing_agent
+            .checkpoint()
+            .factsheet
+            .api_endpoint_schema
+            .is_some());
+        std::fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_managing_agent_project_description() {
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+
+        let managing_agent: ManagingAgent = ManagingAgent::new(
+            "track my workouts".to_string(),
+            Arc::new(provider),
+            test_config(),
+        )
+        .await
+        .expect("Error creating Managing Agent");
+
+        assert!(managing_agent
+            .checkpoint()
+            .factsheet
+            .project_description
+            .starts_with("build a website that"));
+    }
+
+    #[tokio::test]
+    async fn tests_managing_agent_plan_stops_after_architect() {
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+
+        let mut managing_agent: ManagingAgent = ManagingAgent::new(
+            "track my workouts".to_string(),
+            Arc::new(provider),
+            test_config(),
+        )
+        .await
+        .expect("Error creating Managing Agent");
+
+        managing_agent
+            .execute_plan()
             .await
-            .expect("Error creating Managing Agent");
+            .expect("Failed to plan project");
+
+        // Nothing is left to be scheduled again by execute_project
+        assert!(managing_agent.agents.is_empty());
+        assert!(managing_agent
+            .checkpoint()
+            .factsheet
+            .project_scope
+            .is_some());
+        assert!(managing_agent.checkpoint().factsheet.backend_code.is_none());
+    }
+
+    #[tokio::test]
+    async fn tests_managing_agent_declined_run_keeps_factsheet() {
+        let (output_dir, config) = temp_workspace_config("managing_declined");
+        let provider: MockProvider =
+            MockProvider::from_default_fixtures().expect("Failed to load LLM fixtures");
+
+        let mut managing_agent: ManagingAgent = ManagingAgent::new(
+            "track my workouts".to_string(),
+            Arc::new(provider),
+            Arc::new(config),
+        )
+        .await
+        .expect("Error creating Managing Agent");
+        managing_agent.set_approval_hook(ApprovalPolicy::Deny.hook());
+
+        let res: Result<(), WizError> = managing_agent.execute_project().await;
+
+        assert!(res.unwrap_err().is_user_abort());
+        assert!(managing_agent
+            .checkpoint()
+            .factsheet
+            .project_scope
+            .is_some());
+        let backend_code: &str = managing_agent
+            .checkpoint()
+            .factsheet
+            .backend_code
+            .as_deref()
+            .expect("Backend code should be kept");
+        assert!(backend_code.contains("use actix_cors::Cors;"));
+        // The fixture scope requires crud, so main.rs uses the persistence layer
+        assert!(backend_code.starts_with("#[allow(dead_code)]\nmod persistence;"));
+        assert!(managing_agent
+            .checkpoint()
+            .factsheet
+            .persistence_code
+            .is_some());
+        assert!(managing_agent.checkpoint().factsheet.auth_code.is_none());
+        assert_eq!(
+            managing_agent
+                .checkpoint()
+                .agent_state(AgentBackendDeveloper::POSITION),
+            Some(AgentState::UnitTesting)
+        );
+
+        std::fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tests_managing_agent_resume_skips_finished_agents() {
+        let mut checkpoint: Checkpoint = Checkpoint::new(FactSheet {
+            project_description: "build a website that says hello".to_string(),
+            ..Default::default()
+        });
+        checkpoint.record_agent(AgentSolutionArchitect::POSITION, AgentState::Finished);
+
+        // Nothing scripted: any LLM call would fail
+        let mut managing_agent: ManagingAgent = ManagingAgent::from_checkpoint(
+            checkpoint,
+            Arc::new(MockProvider::new()),
+            test_config(),
+        );
+
+        managing_agent
+            .execute_plan()
+            .await
+            .expect("Finished agents should not run again");
+        assert!(managing_agent
+            .checkpoint()
+            .factsheet
+            .project_scope
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn tests_managing_agent_reports_failed_agent() {
+        // No backend responses scripted - the Backend Developer cannot complete
+        let provider: MockProvider = MockProvider::new()
+            .with_response("convert_user_input_to_goal", "build a website that says hello")
+            .with_response(
+                "print_project_scope",
+                r#"{"is_crud_required": false, "is_user_login_and_logout": false, "is_external_urls_required": false}"#,
+            );
+
+        let mut managing_agent: ManagingAgent =
+            ManagingAgent::new("say hello".to_string(), Arc::new(provider), test_config())
+                .await
+                .expect("Error creating Managing Agent");
 
-        managing_agent.execute_project().await;
+        match managing_agent.execute_project().await {
+            Err(WizError::Agent { position, .. }) => {
+                assert_eq!(position, AgentBackendDeveloper::POSITION)
+            }
+            other => panic!("Expected Backend Developer to fail, got {:?}", other),
+        }
+        assert!(managing_agent
+            .checkpoint()
+            .factsheet
+            .project_scope
+            .is_some());
+    }
+
+    // Stand-in agent which only records that it ran
+    #[derive(Debug)]
+    struct ScriptedAgent {
+        attributes: BasicAgent,
+        requires: Vec<FactSheetField>,
+        produces: Vec<FactSheetField>,
+        // Waits until every agent sharing the barrier runs at the same time
+        barrier: Option<Arc<tokio::sync::Barrier>>,
+        fails: bool,
+        ran: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl ScriptedAgent {
+        fn new(
+            position: &str,
+            requires: Vec<FactSheetField>,
+            produces: Vec<FactSheetField>,
+            ran: &Arc<std::sync::Mutex<Vec<String>>>,
+        ) -> Self {
+            Self {
+                attributes: BasicAgent {
+                    objective: "test".to_string(),
+                    position: position.to_string(),
+                    state: AgentState::Discovery,
+                },
+                requires,
+                produces,
+                barrier: None,
+                fails: false,
+                ran: ran.clone(),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SpecialFunctions for ScriptedAgent {
+        fn get_attributes_from_agent(&self) -> &BasicAgent {
+            &self.attributes
+        }
+
+        fn set_agent_state(&mut self, state: AgentState) {
+            self.attributes.state = state;
+        }
+
+        fn requires(&self) -> Vec<FactSheetField> {
+            self.requires.clone()
+        }
+
+        fn produces(&self) -> Vec<FactSheetField> {
+            self.produces.clone()
+        }
+
+        async fn execute(&mut self, factsheet: &mut FactSheet) -> Result<(), WizError> {
+            self.ran
+                .lock()
+                .unwrap()
+                .push(self.attributes.position.clone());
+            if let Some(barrier) = &self.barrier {
+                barrier.wait().await;
+            }
+            if self.fails {
+                return Err(WizError::BuildFailure {
+                    attempts: 3,
+                    errors: "does not compile".to_string(),
+                });
+            }
+
+            for field in &self.produces {
+                match field {
+                    FactSheetField::BackendCode => {
+                        factsheet.backend_code = Some(self.attributes.position.clone())
+                    }
+                    FactSheetField::FrontendCode => {
+                        factsheet.frontend_code = Some(self.attributes.position.clone())
+                    }
+                    FactSheetField::ContractCode => {
+                        factsheet.contract_code = Some(self.attributes.position.clone())
+                    }
+                    _ => {}
+                }
+            }
+            self.attributes.state = AgentState::Finished;
+            Ok(())
+        }
+    }
+
+    fn scripted_manager() -> ManagingAgent {
+        ManagingAgent::from_factsheet(
+            FactSheet {
+                project_description: "build a website that says hello".to_string(),
+                ..Default::default()
+            },
+            Arc::new(MockProvider::new()),
+            test_config(),
+        )
+    }
+
+    #[tokio::test]
+    async fn tests_independent_agents_run_concurrently() {
+        let ran: Arc<std::sync::Mutex<Vec<String>>> = Arc::default();
+        let barrier: Arc<tokio::sync::Barrier> = Arc::new(tokio::sync::Barrier::new(2));
+
+        let mut frontend: ScriptedAgent = ScriptedAgent::new(
+            "Frontend",
+            vec![FactSheetField::BackendCode],
+            vec![FactSheetField::FrontendCode],
+            &ran,
+        );
+        frontend.barrier = Some(barrier.clone());
+        let mut contract: ScriptedAgent = ScriptedAgent::new(
+            "Contract",
+            vec![FactSheetField::BackendCode],
+            vec![FactSheetField::ContractCode],
+            &ran,
+        );
+        contract.barrier = Some(barrier);
+
+        // Added out of order: the backend still runs first
+        let mut managing_agent: ManagingAgent = scripted_manager();
+        managing_agent.add_agent(Box::new(frontend));
+        managing_agent.add_agent(Box::new(contract));
+        managing_agent.add_agent(Box::new(ScriptedAgent::new(
+            "Backend",
+            vec![FactSheetField::ProjectDescription],
+            vec![FactSheetField::BackendCode],
+            &ran,
+        )));
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            managing_agent.execute_agents(),
+        )
+        .await
+        .expect("Frontend and Contract should run at the same time")
+        .expect("Every agent succeeds");
+
+        assert_eq!(ran.lock().unwrap()[0], "Backend");
+        let factsheet: &FactSheet = &managing_agent.checkpoint().factsheet;
+        assert_eq!(factsheet.backend_code.as_deref(), Some("Backend"));
+        assert_eq!(factsheet.frontend_code.as_deref(), Some("Frontend"));
+        assert_eq!(factsheet.contract_code.as_deref(), Some("Contract"));
+    }
+
+    #[tokio::test]
+    async fn tests_dependents_of_failed_agent_are_skipped() {
+        let ran: Arc<std::sync::Mutex<Vec<String>>> = Arc::default();
+
+        let mut backend: ScriptedAgent =
+            ScriptedAgent::new("Backend", vec![], vec![FactSheetField::BackendCode], &ran);
+        backend.fails = true;
+
+        let mut managing_agent: ManagingAgent = scripted_manager();
+        managing_agent.add_agent(Box::new(backend));
+        managing_agent.add_agent(Box::new(ScriptedAgent::new(
+            "Frontend",
+            vec![FactSheetField::BackendCode],
+            vec![FactSheetField::FrontendCode],
+            &ran,
+        )));
+        managing_agent.add_agent(Box::new(ScriptedAgent::new(
+            "Integration",
+            vec![FactSheetField::FrontendCode],
+            vec![],
+            &ran,
+        )));
+        managing_agent.add_agent(Box::new(ScriptedAgent::new(
+            "Contract",
+            vec![FactSheetField::ProjectDescription],
+            vec![FactSheetField::ContractCode],
+            &ran,
+        )));
+
+        match managing_agent.execute_agents().await {
+            Err(WizError::Agent { position, .. }) => assert_eq!(position, "Backend"),
+            other => panic!("Expected Backend to fail, got {:?}", other),
+        }
 
-        dbg!(managing_agent.factsheet);
+        let mut ran: Vec<String> = ran.lock().unwrap().clone();
+        ran.sort();
+        assert_eq!(ran, vec!["Backend", "Contract"]);
+        assert_eq!(
+            managing_agent
+                .checkpoint()
+                .factsheet
+                .contract_code
+                .as_deref(),
+            Some("Contract")
+        );
     }
 }
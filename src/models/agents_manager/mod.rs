@@ -1 +1,3 @@
+pub mod checkpoint;
 pub mod managing_agent;
+pub mod project_plan;
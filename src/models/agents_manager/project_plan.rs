@@ -0,0 +1,107 @@
+use crate::helpers::command_line::PrintCommand;
+use crate::models::agents::agent_backend::AgentBackendDeveloper;
+use crate::models::agents::agent_blockchain::AgentBlockchainIntegrator;
+use crate::models::agents::agent_contract::AgentSmartContractDeveloper;
+use crate::models::agents::agent_frontend::AgentFrontendDeveloper;
+use crate::models::agents::agent_layer::BackendLayer;
+use crate::models::agents::agent_traits::ProjectScope;
+
+// Agents and backend layers chosen from the scope of the Solutions Architect
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectPlan {
+    pub layers: Vec<BackendLayer>,
+    pub is_blockchain_required: bool,
+}
+
+impl ProjectPlan {
+    pub fn from_scope(scope: &ProjectScope) -> Self {
+        let mut layers: Vec<BackendLayer> = vec![];
+        if scope.is_crud_required {
+            layers.push(BackendLayer::Persistence);
+        }
+        if scope.is_user_login_and_logout {
+            layers.push(BackendLayer::Auth);
+        }
+
+        Self {
+            layers,
+            is_blockchain_required: scope.is_blockchain_required,
+        }
+    }
+
+    // One line per agent with the reason it runs or is left out
+    pub fn summary(&self) -> Vec<String> {
+        let chosen = |is_chosen: bool| if is_chosen { "runs" } else { "skipped" };
+        let has_layer = |layer: BackendLayer| self.layers.contains(&layer);
+
+        vec![
+            format!(
+                "{} {}: crud required is {}",
+                BackendLayer::Persistence.position(),
+                chosen(has_layer(BackendLayer::Persistence)),
+                has_layer(BackendLayer::Persistence)
+            ),
+            format!(
+                "{} {}: user login and logout is {}",
+                BackendLayer::Auth.position(),
+                chosen(has_layer(BackendLayer::Auth)),
+                has_layer(BackendLayer::Auth)
+            ),
+            format!(
+                "{} runs: every project has a web server",
+                AgentBackendDeveloper::POSITION
+            ),
+            format!(
+                "{} runs: every project has a web app",
+                AgentFrontendDeveloper::POSITION
+            ),
+            format!(
+                "{} and {} {}: blockchain required is {}",
+                AgentSmartContractDeveloper::POSITION,
+                AgentBlockchainIntegrator::POSITION,
+                chosen(self.is_blockchain_required),
+                self.is_blockchain_required
+            ),
+        ]
+    }
+
+    pub fn print(&self) {
+        for line in self.summary() {
+            PrintCommand::Plan.print_agent_message("Project Manager", &line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_plan_follows_project_scope() {
+        let scope: ProjectScope = ProjectScope {
+            is_crud_required: true,
+            is_user_login_and_logout: false,
+            is_external_urls_required: false,
+            is_blockchain_required: false,
+        };
+        let plan: ProjectPlan = ProjectPlan::from_scope(&scope);
+
+        assert_eq!(plan.layers, vec![BackendLayer::Persistence]);
+        assert!(plan.summary()[0].starts_with("Persistence Developer runs"));
+        assert!(plan.summary()[1].starts_with("Auth Developer skipped"));
+        assert!(plan.summary()[4].ends_with("skipped: blockchain required is false"));
+
+        let scope: ProjectScope = ProjectScope {
+            is_user_login_and_logout: true,
+            is_blockchain_required: true,
+            ..scope
+        };
+        let plan: ProjectPlan = ProjectPlan::from_scope(&scope);
+
+        assert_eq!(
+            plan.layers,
+            vec![BackendLayer::Persistence, BackendLayer::Auth]
+        );
+        assert!(plan.is_blockchain_required);
+    }
+}
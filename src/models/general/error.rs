@@ -0,0 +1,192 @@
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+// Errors surfaced by the agent pipeline
+#[derive(Debug)]
+pub enum WizError {
+    // LLM provider could not be reached or returned an unusable reply
+    LlmTransport {
+        message: String,
+    },
+    // LLM provider answered with an http error status
+    LlmHttp {
+        status: u16,
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    // LLM reply could not be decoded into the expected structure
+    Decode {
+        function_name: String,
+        message: String,
+    },
+    // Generated code failed to build
+    BuildFailure {
+        attempts: u8,
+        errors: String,
+    },
+    // Reading or writing a file failed
+    FileIo {
+        path: String,
+        source: io::Error,
+    },
+    // An external command (i.e. cargo) could not be run
+    Process {
+        command: String,
+        source: io::Error,
+    },
+    // Another process already listens on the port the generated server needs
+    PortInUse {
+        port: u16,
+    },
+    // The HTTP client for testing URLs and endpoints could not be built
+    HttpClient {
+        message: String,
+    },
+    // The local dev chain could not be started or did not answer JSON-RPC
+    DevChain {
+        message: String,
+    },
+    // wizai.toml or the command line options are invalid
+    Config {
+        message: String,
+    },
+    // Endpoints of the generated server failed or answered unexpectedly
+    EndpointTests {
+        failed_routes: Vec<String>,
+    },
+    // The OpenAPI document of the API schema breaks the OpenAPI 3.1 rules
+    InvalidOpenApi {
+        errors: Vec<String>,
+    },
+    // The user declined to continue
+    UserAbort {
+        reason: String,
+    },
+    // Wraps the error of the agent which failed
+    Agent {
+        position: String,
+        source: Box<WizError>,
+    },
+}
+
+impl WizError {
+    pub fn llm_transport(message: impl fmt::Display) -> Self {
+        Self::LlmTransport {
+            message: message.to_string(),
+        }
+    }
+
+    pub fn file_io(path: impl fmt::Display, source: io::Error) -> Self {
+        Self::FileIo {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    pub fn dev_chain(message: impl fmt::Display) -> Self {
+        Self::DevChain {
+            message: message.to_string(),
+        }
+    }
+
+    pub fn process(command: &str, source: io::Error) -> Self {
+        Self::Process {
+            command: command.to_string(),
+            source,
+        }
+    }
+
+    // Transient failures worth retrying: network errors, timeouts, rate limits and server errors
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::LlmTransport { .. } => true,
+            Self::LlmHttp { status, .. } => matches!(status, 408 | 409 | 429 | 500..=599),
+            _ => false,
+        }
+    }
+
+    pub fn is_user_abort(&self) -> bool {
+        match self {
+            Self::UserAbort { .. } => true,
+            Self::Agent { source, .. } => source.is_user_abort(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for WizError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LlmTransport { message } => write!(f, "LLM request failed: {}", message),
+            Self::LlmHttp {
+                status, message, ..
+            } => write!(f, "LLM request failed with HTTP {}: {}", status, message),
+            Self::Decode {
+                function_name,
+                message,
+            } => write!(
+                f,
+                "Failed to decode response of {}: {}",
+                function_name, message
+            ),
+            Self::BuildFailure { attempts, errors } => write!(
+                f,
+                "Generated code failed to build after {} attempts:\n{}",
+                attempts, errors
+            ),
+            Self::FileIo { path, source } => write!(f, "File error on {}: {}", path, source),
+            Self::Process { command, source } => {
+                write!(f, "Failed to run '{}': {}", command, source)
+            }
+            Self::PortInUse { port } => write!(
+                f,
+                "Port {} is already in use, stop the process listening on it or change [server] port",
+                port
+            ),
+            Self::HttpClient { message } => write!(f, "Failed to build HTTP client: {}", message),
+            Self::DevChain { message } => write!(f, "Local dev chain failed: {}", message),
+            Self::Config { message } => write!(f, "Invalid configuration: {}", message),
+            Self::EndpointTests { failed_routes } => {
+                write!(f, "Endpoint tests failed: {}", failed_routes.join("; "))
+            }
+            Self::InvalidOpenApi { errors } => {
+                write!(f, "OpenAPI document is invalid: {}", errors.join("; "))
+            }
+            Self::UserAbort { reason } => write!(f, "Stopped by user: {}", reason),
+            Self::Agent { position, source } => write!(f, "{} failed: {}", position, source),
+        }
+    }
+}
+
+impl std::error::Error for WizError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::FileIo { source, .. } => Some(source),
+            Self::Process { source, .. } => Some(source),
+            Self::Agent { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_agent_error_reports_position_and_cause() {
+        let err: WizError = WizError::Agent {
+            position: "Backend Developer".to_string(),
+            source: Box::new(WizError::UserAbort {
+                reason: "code not approved".to_string(),
+            }),
+        };
+
+        assert!(err.is_user_abort());
+        assert_eq!(
+            err.to_string(),
+            "Backend Developer failed: Stopped by user: code not approved"
+        );
+    }
+}
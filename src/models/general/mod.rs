@@ -1 +1,3 @@
+pub mod error;
 pub mod llm;
+pub mod route;
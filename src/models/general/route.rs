@@ -0,0 +1,385 @@
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// Method of an endpoint, read case-insensitively and written lowercase like print_rest_api_endpoints does
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+    Options,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::Post => "post",
+            Self::Put => "put",
+            Self::Patch => "patch",
+            Self::Delete => "delete",
+            Self::Head => "head",
+            Self::Options => "options",
+        }
+    }
+
+    pub fn has_request_body(&self) -> bool {
+        matches!(self, Self::Post | Self::Put | Self::Patch)
+    }
+}
+
+impl TryFrom<String> for HttpMethod {
+    type Error = String;
+
+    fn try_from(method: String) -> Result<Self, Self::Error> {
+        match method.trim().to_lowercase().as_str() {
+            "get" => Ok(Self::Get),
+            "post" => Ok(Self::Post),
+            "put" => Ok(Self::Put),
+            "patch" => Ok(Self::Patch),
+            "delete" => Ok(Self::Delete),
+            "head" => Ok(Self::Head),
+            "options" => Ok(Self::Options),
+            _ => Err(format!("unsupported HTTP method `{}`", method)),
+        }
+    }
+}
+
+impl From<HttpMethod> for String {
+    fn from(method: HttpMethod) -> Self {
+        method.as_str().to_string()
+    }
+}
+
+impl From<HttpMethod> for reqwest::Method {
+    fn from(method: HttpMethod) -> Self {
+        match method {
+            HttpMethod::Get => Self::GET,
+            HttpMethod::Post => Self::POST,
+            HttpMethod::Put => Self::PUT,
+            HttpMethod::Patch => Self::PATCH,
+            HttpMethod::Delete => Self::DELETE,
+            HttpMethod::Head => Self::HEAD,
+            HttpMethod::Options => Self::OPTIONS,
+        }
+    }
+}
+
+// Any case and surrounding spaces, like TryFrom<String>
+impl JsonSchema for HttpMethod {
+    fn schema_name() -> String {
+        "HttpMethod".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(r"(?i)^\s*(get|post|put|patch|delete|head|options)\s*$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+// i.e. GET, as in request logs
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str().to_uppercase())
+    }
+}
+
+// Shape of a request body or response
+// Read from the type names of print_rest_api_endpoints: "number", "string", "bool", objects of
+// fields and arrays of one item, with "None" for no body and "not_provided" when it is unknown
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(from = "Value", into = "Value")]
+pub enum FieldType {
+    #[default]
+    None,
+    Any,
+    String,
+    Number,
+    Integer,
+    Bool,
+    Array(Box<FieldType>),
+    Object(BTreeMap<String, FieldType>),
+}
+
+impl FieldType {
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self, Self::Array(_) | Self::Object(_))
+    }
+
+    // Type names of the model and Rust primitives, Any when the name is unknown
+    pub fn from_type_name(type_name: &str) -> Self {
+        match type_name.trim().to_lowercase().as_str() {
+            "none" | "null" | "" | "()" => Self::None,
+            "string" | "str" | "&str" | "text" => Self::String,
+            "number" | "float" | "f32" | "f64" | "decimal" => Self::Number,
+            "integer" | "int" | "u8" | "u16" | "u32" | "u64" | "usize" | "i8" | "i16" | "i32"
+            | "i64" | "isize" => Self::Integer,
+            "bool" | "boolean" => Self::Bool,
+            _ => Self::Any,
+        }
+    }
+
+    // Whether two descriptions of one body can be the same: Any agrees with everything and
+    // numbers with integers, since the model writes "number" for every numeric field
+    pub fn agrees_with(&self, other: &FieldType) -> bool {
+        match (self, other) {
+            (Self::Any, _) | (_, Self::Any) => true,
+            (Self::Number | Self::Integer, Self::Number | Self::Integer) => true,
+            (Self::Array(item), Self::Array(other_item)) => item.agrees_with(other_item),
+            (Self::Object(fields), Self::Object(other_fields)) => {
+                fields.len() == other_fields.len()
+                    && fields.iter().all(|(name, field)| {
+                        other_fields
+                            .get(name)
+                            .is_some_and(|other_field: &FieldType| field.agrees_with(other_field))
+                    })
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl From<Value> for FieldType {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Self::None,
+            Value::String(type_name) => Self::from_type_name(&type_name),
+            Value::Array(items) => Self::Array(Box::new(
+                items.into_iter().next().map_or(Self::Any, Self::from),
+            )),
+            Value::Object(fields) => Self::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, field)| (name, Self::from(field)))
+                    .collect(),
+            ),
+            // Sample values instead of type names
+            Value::Bool(_) => Self::Bool,
+            Value::Number(number) if number.is_f64() => Self::Number,
+            Value::Number(_) => Self::Integer,
+        }
+    }
+}
+
+impl From<FieldType> for Value {
+    fn from(field_type: FieldType) -> Self {
+        match field_type {
+            FieldType::None => Value::String("None".to_string()),
+            FieldType::Any => Value::String("not_provided".to_string()),
+            FieldType::String => Value::String("string".to_string()),
+            FieldType::Number => Value::String("number".to_string()),
+            FieldType::Integer => Value::String("integer".to_string()),
+            FieldType::Bool => Value::String("bool".to_string()),
+            FieldType::Array(item) => Value::Array(vec![Value::from(*item)]),
+            FieldType::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, field)| (name, Value::from(field)))
+                    .collect::<Map<String, Value>>(),
+            ),
+        }
+    }
+}
+
+// Every JSON value reads as a FieldType, the schema only describes what is expected
+impl JsonSchema for FieldType {
+    fn schema_name() -> String {
+        "FieldType".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "\"None\", a type name (\"string\", \"number\", \"bool\"), an object of field types or an array of one item type"
+                        .to_string(),
+                ),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+// Type names as the model writes them, i.e. number or {"id":"number"}
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Value::from(self.clone()) {
+            Value::String(type_name) => write!(f, "{}", type_name),
+            value => write!(f, "{}", value),
+        }
+    }
+}
+
+// Name of a path parameter segment: `{id}` and actix patterns like `{id:\d+}` give `id`
+pub fn path_parameter(segment: &str) -> Option<&str> {
+    let inner: &str = segment.strip_prefix('{')?.strip_suffix('}')?;
+    inner.split(':').next()
+}
+
+// Route with the parameter names left out, so /task/{id} and /task/{task_id} are the same route
+pub fn route_key(route: &str) -> String {
+    route
+        .trim_end_matches('/')
+        .split('/')
+        .map(|segment: &str| match path_parameter(segment) {
+            Some(_) => "{}",
+            None => segment,
+        })
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+// Accepts true and false as booleans or strings, the model quotes every value
+pub fn deserialize_flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Bool(flag) => Ok(flag),
+        Value::String(flag) => match flag.trim().to_lowercase().as_str() {
+            "true" | "yes" => Ok(true),
+            "false" | "no" | "" => Ok(false),
+            _ => Err(serde::de::Error::custom(format!(
+                "expected true or false, got `{}`",
+                flag
+            ))),
+        },
+        Value::Null => Ok(false),
+        other => Err(serde::de::Error::custom(format!(
+            "expected true or false, got {}",
+            other
+        ))),
+    }
+}
+
+// What deserialize_flag accepts
+pub fn flag_schema(_: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+        instance_type: Some(
+            vec![
+                InstanceType::Boolean,
+                InstanceType::String,
+                InstanceType::Null,
+            ]
+            .into(),
+        ),
+        ..Default::default()
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::json_decode::{decode_response, DecodeFailure};
+    use crate::models::agents::agent_traits::RouteObject;
+    use serde_json::json;
+
+    #[test]
+    fn tests_route_object_from_model_output() {
+        let endpoints: Vec<RouteObject> = serde_json::from_value(json!([
+            {
+                "route": "/item/{id:\\d+}",
+                "is_route_dynamic": "True",
+                "method": " GET",
+                "request_body": "None",
+                "response": {"id": "number", "tags": ["string"], "done": "bool", "owner": "u64"}
+            },
+            {
+                "route": "/item",
+                "is_route_dynamic": false,
+                "method": "post",
+                "request_body": {"name": "string"},
+                "response": "not_provided"
+            },
+            {"route": "/crypto", "method": "get"}
+        ]))
+        .unwrap();
+
+        assert_eq!(endpoints[0].method, HttpMethod::Get);
+        assert!(endpoints[0].is_route_dynamic);
+        assert_eq!(endpoints[0].path_parameters(), vec!["id"]);
+        assert_eq!(endpoints[0].request_body, FieldType::None);
+        assert_eq!(
+            endpoints[0].response,
+            FieldType::Object(BTreeMap::from([
+                ("done".to_string(), FieldType::Bool),
+                ("id".to_string(), FieldType::Number),
+                ("owner".to_string(), FieldType::Integer),
+                (
+                    "tags".to_string(),
+                    FieldType::Array(Box::new(FieldType::String))
+                ),
+            ]))
+        );
+        assert_eq!(endpoints[1].response, FieldType::Any);
+        assert!(!endpoints[2].is_route_dynamic);
+        assert_eq!(endpoints[2].response, FieldType::None);
+
+        // Written back in the vocabulary of the model
+        let written: Value = serde_json::to_value(&endpoints[1]).unwrap();
+        assert_eq!(
+            written,
+            json!({
+                "route": "/item",
+                "is_route_dynamic": false,
+                "method": "post",
+                "request_body": {"name": "string"},
+                "response": "not_provided"
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RouteObject>(written).unwrap(),
+            endpoints[1]
+        );
+
+        let unknown: Result<RouteObject, serde_json::Error> =
+            serde_json::from_value(json!({"route": "/", "method": "fetch"}));
+        assert!(unknown
+            .unwrap_err()
+            .to_string()
+            .contains("unsupported HTTP method `fetch`"));
+    }
+
+    #[test]
+    fn tests_route_object_schema() {
+        let fixture: &str = include_str!("../../../fixtures/llm/print_rest_api_endpoints.txt");
+        let endpoints: Vec<RouteObject> = decode_response(fixture).unwrap();
+        assert!(!endpoints.is_empty());
+
+        // The flag may be a string and the reply may end in a trailing comma
+        let endpoints: Vec<RouteObject> = decode_response(
+            r#"[{"route": "/item/{id}", "is_route_dynamic": "True", "method": "Delete",},]"#,
+        )
+        .unwrap();
+        assert_eq!(endpoints[0].method, HttpMethod::Delete);
+
+        let failure: DecodeFailure = decode_response::<Vec<RouteObject>>(
+            r#"[{"route": "/", "is_route_dynamic": 1, "method": "fetch"}]"#,
+        )
+        .unwrap_err();
+        assert!(failure
+            .errors
+            .iter()
+            .any(|error: &String| error.starts_with("/0/method: \"fetch\" does not match")));
+        assert!(failure
+            .errors
+            .iter()
+            .any(|error: &String| error.starts_with("/0/is_route_dynamic: 1 is not of type")));
+    }
+}